@@ -5,20 +5,32 @@
 
 use std::{
     borrow::Cow,
-    env, io,
+    collections::HashMap,
+    env, fs,
+    io::{self, Write},
     path::{Path, PathBuf},
+    process,
 };
 
 use anyhow::{Context as _, bail};
 use clap::{Parser, Subcommand, ValueEnum};
-use futures_util::StreamExt as _;
+use futures_util::{StreamExt as _, TryStreamExt as _};
 use log::LevelFilter;
-use sqlx::{SqliteExecutor, SqlitePool};
+use relative_path::RelativePath;
+use serde::Serialize;
+use sqlx::{SqliteExecutor, SqlitePool, types::Uuid};
+use time::{Date, Month};
+use tokio::task::JoinHandle;
 
 use endjine::{
-    AlbumArt, BatchOutcome, DbUuid, FilePath, Historylist, HistorylistEntity, Information,
-    LibraryPath, PerformanceData, Playlist, PlaylistEntity, PreparelistEntity, Smartlist, Track,
-    batch, open_database, resolve_playlist_track_refs_from_file_paths,
+    AlbumArt, BatchOutcome, DbUuid, FilePath, ForeignKeyViolation, Historylist, HistorylistEntity,
+    Information, LibraryPath, M3uWriter, OpenDatabaseOptions, PLAYLIST_PATH_SEGMENT_SEPARATOR,
+    PerformanceData, Playlist, PlaylistEntity, PlaylistId, PlaylistPath, PreparelistEntity, Rating,
+    Smartlist, SmartlistUuid, Track, TrackFilter, TrackId, UnixTimestamp, batch,
+    check_foreign_keys, check_integrity, export_playlist_to_pls, export_playlist_to_xspf,
+    import_pls_file, import_track_file_path, import_xspf_file, is_valid_playlist_path_segment,
+    open_database, open_database_read_only, read_pls, read_xspf,
+    resolve_playlist_track_refs_from_file_paths, split_playlist_path_into_segments,
 };
 
 /// Default log level for debug builds.
@@ -34,15 +46,43 @@ const DEFAULT_DB_FILE: &str = "m.db";
 #[derive(Debug, Subcommand)]
 enum Command {
     /// Scan database for consistency and missing or inaccessible track files (read-only).
-    Analyze,
+    Analyze(AnalyzeArgs),
+    /// Print library statistics (read-only).
+    Stats,
+    /// Run all consistency checks and exit with code 1 if any issue is found (read-only).
+    Validate,
     /// Find missing or inaccessible track files (read-only).
-    FindMissingTracks,
-    /// Import playlist from M3U file.
+    FindMissingTracks(FindMissingTracksArgs),
+    /// Find tracks that share the same file path (read-only unless deleting).
+    FindDuplicates(FindDuplicatesArgs),
+    /// Create an empty playlist.
+    CreatePlaylist(CreatePlaylistArgs),
+    /// Rename a playlist.
+    RenamePlaylist(RenamePlaylistArgs),
+    /// Append the tracks of a playlist to another playlist.
+    MergePlaylists(MergePlaylistsArgs),
+    /// Delete a playlist.
+    DeletePlaylist(DeletePlaylistArgs),
+    /// List all playlists (read-only).
+    ListPlaylists(ListPlaylistsArgs),
+    /// List tracks, optionally filtered (read-only).
+    ListTracks(ListTracksArgs),
+    /// Show details of a single track (read-only).
+    ShowTrack(ShowTrackArgs),
+    /// Export playlist to M3U, PLS, or XSPF file (read-only).
+    ExportPlaylist(ExportPlaylistArgs),
+    /// Export the play history to CSV or M3U file (read-only).
+    ExportHistory(ExportHistoryArgs),
+    /// Import playlist from M3U, PLS, or XSPF file.
     ImportPlaylist(ImportPlaylistArgs),
     /// Delete all empty playlists.
     DeleteEmptyPlaylists,
+    /// Delete a smartlist.
+    DeleteSmartlist(DeleteSmartlistArgs),
+    /// Replace the common prefix of track paths after relocating the music folder.
+    RebasePaths(RebasePathsArgs),
     /// Convert album art images from PNG to JPG to save space.
-    ShrinkAlbumArt,
+    ShrinkAlbumArt(ShrinkAlbumArtArgs),
     /// Purge all album art for re-import.
     PurgeAlbumArt,
     /// Purge cruft from the database.
@@ -51,6 +91,33 @@ enum Command {
     Optimize,
 }
 
+impl Command {
+    const fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Self::Analyze(_)
+                | Self::Validate
+                | Self::Stats
+                | Self::FindMissingTracks(_)
+                | Self::ListPlaylists(_)
+                | Self::ListTracks(_)
+                | Self::ShowTrack(_)
+                | Self::ExportHistory(_)
+                | Self::ExportPlaylist(_)
+                | Self::FindDuplicates(FindDuplicatesArgs { delete: false })
+                | Self::RebasePaths(RebasePathsArgs { dry_run: true, .. })
+        )
+    }
+
+    const fn is_json_output(&self) -> bool {
+        matches!(
+            self,
+            Self::Analyze(AnalyzeArgs { json: true })
+                | Self::FindMissingTracks(FindMissingTracksArgs { json: true, .. })
+        )
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default, ValueEnum)]
 enum ImportPlaylistMode {
     /// Appends tracks to a playlist.
@@ -60,23 +127,315 @@ enum ImportPlaylistMode {
     Replace,
 }
 
+impl From<ImportPlaylistMode> for endjine::ImportPlaylistMode {
+    fn from(from: ImportPlaylistMode) -> Self {
+        match from {
+            ImportPlaylistMode::Append => Self::Append,
+            ImportPlaylistMode::Replace => Self::Replace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum PlaylistFileFormat {
+    /// M3U with one file path per line.
+    #[default]
+    M3u,
+    /// PLS with numbered file, title, and length entries.
+    Pls,
+    /// XML Shareable Playlist Format.
+    Xspf,
+}
+
+impl PlaylistFileFormat {
+    /// Guesses the format from the file extension.
+    fn from_file_path(file_path: &Path) -> Option<Self> {
+        let extension = file_path.extension()?.to_str()?;
+        if extension.eq_ignore_ascii_case("m3u") || extension.eq_ignore_ascii_case("m3u8") {
+            Some(Self::M3u)
+        } else if extension.eq_ignore_ascii_case("pls") {
+            Some(Self::Pls)
+        } else if extension.eq_ignore_ascii_case("xspf") {
+            Some(Self::Xspf)
+        } else {
+            None
+        }
+    }
+
+    /// Resolves an optional format argument.
+    ///
+    /// Defaults to the format of the file extension, if any.
+    fn resolve(format: Option<Self>, file_path: Option<&Path>) -> Self {
+        format
+            .or_else(|| file_path.and_then(Self::from_file_path))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+enum HistoryExportFormat {
+    /// All matching history entries with session, time, and track.
+    #[default]
+    Csv,
+    /// Track file paths of a single session.
+    M3u,
+}
+
+#[derive(Debug, Parser)]
+struct AnalyzeArgs {
+    /// Print a JSON report to stdout and suppress all log output except errors.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Parser)]
+struct FindMissingTracksArgs {
+    /// Print a JSON report to stdout and suppress all log output except errors.
+    #[arg(long)]
+    json: bool,
+
+    /// Maximum number of track files that are checked concurrently.
+    #[arg(long, default_value_t = batch::DEFAULT_MAX_CONCURRENT_TRACK_FILE_CHECKS)]
+    max_concurrent_checks: usize,
+}
+
+#[derive(Debug, Parser)]
+struct FindDuplicatesArgs {
+    /// Delete all duplicates after confirmation, keeping the track with the lowest ID.
+    #[arg(long)]
+    delete: bool,
+}
+
+#[derive(Debug, Parser)]
+struct RebasePathsArgs {
+    /// Path prefix to replace, relative to the library directory.
+    ///
+    /// Must start with "..", e.g. "../Music".
+    #[arg(long)]
+    old_prefix: String,
+
+    /// Replacement path prefix, relative to the library directory.
+    ///
+    /// Must start with "..", e.g. "../../Volumes/Music".
+    #[arg(long)]
+    new_prefix: String,
+
+    /// Only show what would change without modifying the database.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Parser)]
+struct CreatePlaylistArgs {
+    /// Path in the playlist hierarchy.
+    ///
+    /// Missing parent playlists are created. See `import-playlist`
+    /// for the syntax.
+    ///
+    /// Example: "Parent Title;Child Title"
+    #[arg(long)]
+    playlist_path: String,
+
+    /// Marks the playlist as persisted.
+    ///
+    /// Optional. Defaults to true.
+    #[arg(long)]
+    is_persisted: Option<bool>,
+}
+
+#[derive(Debug, Parser)]
+struct RenamePlaylistArgs {
+    /// Current path in the playlist hierarchy.
+    ///
+    /// See `import-playlist` for the syntax.
+    #[arg(long)]
+    playlist_path: String,
+
+    /// New title, i.e. the last segment of the path.
+    #[arg(long)]
+    new_title: String,
+}
+
+#[derive(Debug, Parser)]
+struct MergePlaylistsArgs {
+    /// Path of the playlist whose tracks are appended.
+    ///
+    /// See `import-playlist` for the syntax.
+    #[arg(long)]
+    source_path: String,
+
+    /// Path of the playlist to which the tracks are appended.
+    ///
+    /// See `import-playlist` for the syntax.
+    #[arg(long)]
+    target_path: String,
+
+    /// Delete the source playlist after merging.
+    #[arg(long)]
+    delete_source: bool,
+}
+
+#[derive(Debug, Parser)]
+struct DeletePlaylistArgs {
+    /// Path in the playlist hierarchy.
+    ///
+    /// See `import-playlist` for the syntax.
+    #[arg(long)]
+    playlist_path: String,
+
+    /// Also delete all children and entries.
+    ///
+    /// Otherwise only empty playlists without children are deleted.
+    #[arg(long)]
+    recursive: bool,
+}
+
+#[derive(Debug, Parser)]
+struct ListPlaylistsArgs {
+    /// Print one playlist path per line instead of a tree.
+    #[arg(long, conflicts_with = "json")]
+    flat: bool,
+
+    /// Print a JSON array instead of a tree.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Parser)]
+struct ListTracksArgs {
+    /// Only include tracks whose artist contains this text.
+    #[arg(long)]
+    artist: Option<String>,
+
+    /// Only include tracks whose title contains this text.
+    #[arg(long)]
+    title: Option<String>,
+
+    /// Only include tracks whose genre contains this text.
+    #[arg(long)]
+    genre: Option<String>,
+
+    /// Minimum analyzed BPM (inclusive).
+    #[arg(long)]
+    bpm_min: Option<i64>,
+
+    /// Maximum analyzed BPM (inclusive).
+    #[arg(long)]
+    bpm_max: Option<i64>,
+
+    /// Minimum rating in stars from 0 to 5 (inclusive).
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=5))]
+    rating_min: Option<u8>,
+
+    /// Only include analyzed tracks.
+    #[arg(long)]
+    analyzed_only: bool,
+
+    /// Print a JSON array instead of tab-separated lines.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Parser)]
+struct ShowTrackArgs {
+    /// Track ID.
+    #[arg(long, required_unless_present = "path", conflicts_with = "path")]
+    track_id: Option<i64>,
+
+    /// Track file path.
+    ///
+    /// Either absolute or relative to the library directory.
+    #[arg(long)]
+    path: Option<String>,
+
+    /// Show the sizes of the performance data.
+    #[arg(long)]
+    performance_data: bool,
+
+    /// List all playlists that contain the track.
+    #[arg(long)]
+    playlists: bool,
+}
+
+#[derive(Debug, Parser)]
+struct ExportPlaylistArgs {
+    /// Path in the playlist hierarchy.
+    ///
+    /// See `import-playlist` for the syntax.
+    #[arg(long)]
+    playlist_path: String,
+
+    /// Output file path.
+    ///
+    /// Optional. Defaults to writing to stdout instead of a file.
+    #[arg(long)]
+    output_file: Option<PathBuf>,
+
+    /// Playlist file format.
+    ///
+    /// Optional. Defaults to the format of the output file extension or "m3u".
+    #[arg(long)]
+    format: Option<PlaylistFileFormat>,
+
+    /// Write extended M3U with duration, artist, and title of each track.
+    ///
+    /// Only applicable to M3U.
+    #[arg(long)]
+    extended: bool,
+}
+
+#[derive(Debug, Parser)]
+struct ExportHistoryArgs {
+    /// First day (inclusive, UTC) formatted as ISO 8601 date, e.g. "2024-12-31".
+    #[arg(long, value_parser = parse_iso_date)]
+    from: Option<Date>,
+
+    /// Last day (inclusive, UTC) formatted as ISO 8601 date, e.g. "2024-12-31".
+    #[arg(long, value_parser = parse_iso_date)]
+    to: Option<Date>,
+
+    /// Only export a single session.
+    ///
+    /// Required for the M3U format.
+    #[arg(long)]
+    session_id: Option<i64>,
+
+    /// Output file path.
+    ///
+    /// Optional. Defaults to writing to stdout instead of a file.
+    #[arg(long)]
+    output_file: Option<PathBuf>,
+
+    /// Output format.
+    ///
+    /// Optional. Defaults to "csv".
+    #[arg(long)]
+    format: Option<HistoryExportFormat>,
+}
+
 #[derive(Debug, Parser)]
 struct ImportPlaylistArgs {
-    /// M3U file path.
+    /// Playlist file path.
     ///
     /// Optional. Defaults to reading from stdin instead of a file.
-    #[arg(long)]
-    m3u_file: Option<PathBuf>,
+    #[arg(long, alias = "m3u-file")]
+    file: Option<PathBuf>,
+
+    /// Absolute base path for resolving relative file paths in the playlist.
+    ///
+    /// Optional. Defaults to the parent directory of the playlist file.
+    #[arg(long, alias = "m3u-base-path")]
+    base_path: Option<PathBuf>,
 
-    /// Absolute base path for resolving relative M3U file paths.
+    /// Playlist file format.
     ///
-    /// Optional. Defaults to the parent directory of the M3U file.
+    /// Optional. Defaults to the format of the file extension or "m3u".
     #[arg(long)]
-    m3u_base_path: Option<PathBuf>,
+    format: Option<PlaylistFileFormat>,
 
     /// Path in the playlist hierarchy.
     ///
-    /// Optional. Defaults to the M3U file name without extension.
+    /// Optional. Defaults to the playlist file name without extension.
     ///
     /// The playlist path in Engine DJ is composed from the playlist titles
     /// in the library hierarchy. Path segments are separated by semicolons (';').
@@ -93,6 +452,36 @@ struct ImportPlaylistArgs {
     mode: Option<ImportPlaylistMode>,
 }
 
+#[derive(Debug, Parser)]
+struct ShrinkAlbumArtArgs {
+    /// Quality of the re-encoded JPEG images (1-100).
+    ///
+    /// Optional. Defaults to low quality.
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=100))]
+    jpeg_quality: Option<u8>,
+
+    /// Maximum width and height of images in pixels.
+    ///
+    /// Optional. Larger images are scaled down while preserving their aspect ratio.
+    #[arg(long)]
+    max_dimension: Option<u32>,
+
+    /// Minimum size of images in bytes.
+    ///
+    /// Optional. Smaller images are not considered.
+    #[arg(long)]
+    min_image_size: Option<u64>,
+}
+
+#[derive(Debug, Parser)]
+struct DeleteSmartlistArgs {
+    /// UUID of the smartlist.
+    ///
+    /// Materialized playlists with the same name are not deleted.
+    #[arg(long)]
+    uuid: Uuid,
+}
+
 #[derive(Debug, Parser)]
 struct Args {
     #[arg(long)]
@@ -112,6 +501,9 @@ async fn main() -> anyhow::Result<()> {
         .init();
 
     let Args { db_file, command } = Args::parse();
+    if command.is_json_output() {
+        log::set_max_level(LevelFilter::Error);
+    }
 
     let mut db_file_path = db_file.map_or(Cow::Borrowed(Path::new(DEFAULT_DB_FILE)), Cow::Owned);
     if db_file_path.is_relative() {
@@ -121,7 +513,12 @@ async fn main() -> anyhow::Result<()> {
     }
     debug_assert!(db_file_path.is_absolute());
 
-    let (pool, _info) = match open_database(&db_file_path, None).await {
+    let open_result = if command.is_read_only() {
+        open_database_read_only(&db_file_path).await
+    } else {
+        open_database(&db_file_path, None, &OpenDatabaseOptions::default()).await
+    };
+    let (pool, _info) = match open_result {
         Ok(pool) => {
             log::info!(
                 "Opened database file \"{db_file_path}\"",
@@ -150,69 +547,328 @@ async fn main() -> anyhow::Result<()> {
     };
     log::info!("Library directory: {library_path}");
 
-    let info = Information::load(|| &pool).await?;
+    let info = Information::load(&mut *pool.acquire().await?).await?;
     log::info!("Database UUID: {uuid}", uuid = info.uuid());
 
     match command {
-        Command::Analyze => {
-            track_scan(&pool).await;
-            playlist_scan(&pool).await;
-            playlist_entity_scan(&pool).await;
-            smartlist_scan(&pool).await;
-            preparelist_entity_scan(&pool).await;
-            if historylist_scan(&pool).await {
-                historylist_entity_scan(&pool).await;
+        Command::Analyze(AnalyzeArgs { json }) => {
+            let integrity_problems = integrity_check(&pool).await;
+            let foreign_key_violations = foreign_key_check(&pool).await;
+            let track = track_scan(&pool).await;
+            let playlist = playlist_scan(&pool).await;
+            let playlist_entity = playlist_entity_scan(&pool).await;
+            let smartlist = smartlist_scan(&pool).await;
+            let preparelist_entity = preparelist_entity_scan(&pool).await;
+            let historylist = historylist_scan(&pool).await;
+            let historylist_entity = if historylist.is_some() {
+                Some(historylist_entity_scan(&pool).await)
+            } else {
+                None
+            };
+            let performance_data = performance_data_scan(&pool).await;
+            let orphaned_album_art = orphaned_album_art_scan(&pool).await;
+            if json {
+                let report = AnalyzeReport {
+                    integrity_problems,
+                    foreign_key_violations,
+                    track,
+                    playlist,
+                    playlist_entity,
+                    smartlist,
+                    preparelist_entity,
+                    historylist,
+                    historylist_entity,
+                    performance_data,
+                    orphaned_album_art,
+                };
+                print_json(&report)?;
+            }
+        }
+        Command::Stats => {
+            print_stats(&pool, &db_file_path.to_path(), &info).await?;
+        }
+        Command::Validate => {
+            let issue_count = validate(&pool).await?;
+            if issue_count > 0 {
+                println!("Validation failed: {issue_count} issue(s) found");
+                process::exit(1);
             }
-            performance_data_scan(&pool).await;
+            println!("Validation passed");
         }
-        Command::FindMissingTracks => {
-            find_track_file_issues(&pool, library_path.to_path()).await;
+        Command::FindMissingTracks(FindMissingTracksArgs {
+            json,
+            max_concurrent_checks,
+        }) => {
+            let issues =
+                find_track_file_issues(&pool, library_path.to_path(), max_concurrent_checks).await;
+            if json {
+                let missing = issues
+                    .iter()
+                    .filter(|issue| matches!(issue.issue, TrackFileIssueKind::Missing))
+                    .count();
+                let report = FindMissingTracksReport {
+                    missing,
+                    inaccessible: issues.len() - missing,
+                    issues,
+                };
+                print_json(&report)?;
+            }
         }
         Command::DeleteEmptyPlaylists => {
             playlist_delete_empty(&pool).await;
         }
-        Command::ShrinkAlbumArt => {
-            album_art_shrink_images(&pool).await;
+        Command::DeleteSmartlist(DeleteSmartlistArgs { uuid }) => {
+            smartlist_delete(&pool, &SmartlistUuid::from_uuid(uuid)).await;
+        }
+        Command::ShrinkAlbumArt(ShrinkAlbumArtArgs {
+            jpeg_quality,
+            max_dimension,
+            min_image_size,
+        }) => {
+            let mut options = batch::AlbumArtShrinkOptions::new(endjine::AlbumArtImageQuality::Low);
+            if let Some(jpeg_quality) = jpeg_quality {
+                options.jpeg_quality = jpeg_quality;
+            }
+            options.max_dimension = max_dimension;
+            options.min_image_size = min_image_size;
+            album_art_shrink_images(&pool, options).await;
         }
         Command::PurgeAlbumArt => {
             album_art_purge_images(&pool).await;
         }
+        Command::FindDuplicates(FindDuplicatesArgs { delete }) => {
+            let duplicate_count = find_duplicate_tracks(&pool).await?;
+            if duplicate_count == 0 {
+                println!("No duplicate tracks found");
+            } else if delete {
+                if confirm(&format!("Delete {duplicate_count} duplicate track(s)?"))? {
+                    let deleted_count = batch::delete_duplicate_tracks(&pool).await?;
+                    println!("Deleted {deleted_count} duplicate track(s)");
+                } else {
+                    println!("Aborted");
+                }
+            }
+        }
+        Command::RebasePaths(RebasePathsArgs {
+            old_prefix,
+            new_prefix,
+            dry_run,
+        }) => {
+            let rebased_paths = batch::rebase_track_paths(
+                &pool,
+                RelativePath::new(&old_prefix),
+                RelativePath::new(&new_prefix),
+                dry_run,
+            )
+            .await?;
+            if dry_run {
+                for batch::RebasedTrackPath {
+                    track_id,
+                    old_path,
+                    new_path,
+                } in &rebased_paths
+                {
+                    println!("{track_id}\t{old_path}\t{new_path}");
+                }
+                println!(
+                    "Would rebase {count} track path(s)",
+                    count = rebased_paths.len()
+                );
+            } else {
+                println!("Rebased {count} track path(s)", count = rebased_paths.len());
+            }
+        }
+        Command::CreatePlaylist(CreatePlaylistArgs {
+            playlist_path,
+            is_persisted,
+        }) => {
+            let playlist_id =
+                playlist_create(&pool, &playlist_path, is_persisted.unwrap_or(true)).await?;
+            println!("{playlist_id}");
+        }
+        Command::RenamePlaylist(RenamePlaylistArgs {
+            playlist_path,
+            new_title,
+        }) => {
+            let new_path = playlist_rename(&pool, &playlist_path, &new_title)
+                .await
+                .with_context(|| format!("rename playlist \"{playlist_path}\""))?;
+            println!("{new_path}");
+        }
+        Command::MergePlaylists(MergePlaylistsArgs {
+            source_path,
+            target_path,
+            delete_source,
+        }) => {
+            let (appended_count, skipped_count) =
+                playlist_merge(&pool, &source_path, &target_path, delete_source)
+                    .await
+                    .with_context(|| {
+                        format!("merge playlist \"{source_path}\" into \"{target_path}\"")
+                    })?;
+            println!("Appended {appended_count} track(s), skipped {skipped_count} duplicate(s)");
+        }
+        Command::DeletePlaylist(DeletePlaylistArgs {
+            playlist_path,
+            recursive,
+        }) => {
+            playlist_delete(&pool, &playlist_path, recursive).await?;
+        }
+        Command::ListPlaylists(ListPlaylistsArgs { flat, json }) => {
+            if flat {
+                playlist_list_flat(&pool).await?;
+            } else if json {
+                playlist_list_json(&pool).await?;
+            } else {
+                playlist_list_tree(&pool).await?;
+            }
+        }
+        Command::ListTracks(ListTracksArgs {
+            artist,
+            title,
+            genre,
+            bpm_min,
+            bpm_max,
+            rating_min,
+            analyzed_only,
+            json,
+        }) => {
+            let filter = TrackFilter {
+                artist,
+                title,
+                genre,
+                bpm_min,
+                bpm_max,
+                rating_min: rating_min
+                    .map(|stars| Rating::try_new(stars.into()).expect("valid number of stars")),
+                analyzed_only,
+            };
+            if json {
+                track_list_json(&pool, &filter).await?;
+            } else {
+                track_list_tsv(&pool, &filter).await?;
+            }
+        }
+        Command::ShowTrack(ShowTrackArgs {
+            track_id,
+            path,
+            performance_data,
+            playlists,
+        }) => {
+            let track_id = if let Some(track_id) = track_id {
+                TrackId::new(track_id)
+            } else {
+                let path = path.expect("either track id or path");
+                let file_path = FilePath::try_from_str(&path)?;
+                let track_path = import_track_file_path(&library_path, file_path)?;
+                let Some(track_ref) = Track::find_ref_by_path(&pool, &track_path).await? else {
+                    bail!("track with path \"{track_path}\" not found");
+                };
+                track_ref.id()
+            };
+            show_track(&pool, track_id, performance_data, playlists).await?;
+        }
+        Command::ExportPlaylist(ExportPlaylistArgs {
+            playlist_path,
+            output_file,
+            format,
+            extended,
+        }) => {
+            let format = PlaylistFileFormat::resolve(format, output_file.as_deref());
+            if extended && format != PlaylistFileFormat::M3u {
+                log::warn!("Ignoring extended option for {format:?} format");
+            }
+            let output: Box<dyn Write> = if let Some(output_file) = &output_file {
+                Box::new(io::BufWriter::new(
+                    fs::File::create(output_file).context("create playlist file")?,
+                ))
+            } else {
+                Box::new(io::stdout().lock())
+            };
+            export_playlist(
+                &pool,
+                &library_path,
+                &playlist_path,
+                format,
+                extended,
+                output,
+            )
+            .await
+            .with_context(|| format!("export playlist \"{playlist_path}\""))?;
+        }
+        Command::ExportHistory(ExportHistoryArgs {
+            from,
+            to,
+            session_id,
+            output_file,
+            format,
+        }) => {
+            let output: Box<dyn Write> = if let Some(output_file) = &output_file {
+                Box::new(io::BufWriter::new(
+                    fs::File::create(output_file).context("create output file")?,
+                ))
+            } else {
+                Box::new(io::stdout().lock())
+            };
+            let count = match format.unwrap_or_default() {
+                HistoryExportFormat::Csv => {
+                    let filter = batch::HistoryExportFilter {
+                        from: from.map(utc_midnight_timestamp),
+                        to: to.map(|to| utc_midnight_timestamp(to.next_day().unwrap_or(Date::MAX))),
+                        session_id,
+                    };
+                    batch::export_history_to_csv(&pool, &library_path, &filter, output).await?
+                }
+                HistoryExportFormat::M3u => {
+                    let Some(session_id) = session_id else {
+                        bail!("M3U export requires a session id");
+                    };
+                    if from.is_some() || to.is_some() {
+                        log::warn!("Ignoring date range for M3U export of a single session");
+                    }
+                    Historylist::export_to_m3u(&pool, &library_path, session_id, output).await?
+                }
+            };
+            log::info!("Exported {count} history entries");
+        }
         Command::ImportPlaylist(ImportPlaylistArgs {
             playlist_path,
             mode,
-            m3u_file,
-            m3u_base_path,
+            file,
+            base_path,
+            format,
         }) => {
-            let mode = mode.unwrap_or_default();
+            let mode = mode.unwrap_or_default().into();
+            let format = PlaylistFileFormat::resolve(format, file.as_deref());
             let Some(playlist_path) = playlist_path.map(Cow::Owned).or_else(|| {
-                m3u_file
-                    .as_deref()
+                file.as_deref()
                     .and_then(Path::file_prefix)
                     .and_then(|file_name| file_name.to_str().map(Cow::Borrowed))
             }) else {
                 bail!("Missing playlist path");
             };
             log::info!("Playlist path: {playlist_path}");
-            let source = if let Some(m3u_file) = &m3u_file {
-                Cow::Owned(format!("file \"{}\"", m3u_file.display()))
+            let source = if let Some(file) = &file {
+                Cow::Owned(format!("file \"{}\"", file.display()))
             } else {
                 Cow::Borrowed("stdin")
             };
-            log::info!("Importing M3U playlist from {source}");
-            match import_playlist_from_m3u_file(
+            log::info!("Importing {format:?} playlist from {source}");
+            match import_playlist_from_file(
                 &pool,
                 *info.uuid(),
                 &library_path,
                 &playlist_path,
                 mode,
-                m3u_file.as_deref(),
-                m3u_base_path.as_deref(),
+                format,
+                file.as_deref(),
+                base_path.as_deref(),
             )
             .await
             {
                 Ok(()) => (),
                 Err(err) => {
-                    bail!("Failed to import M3U playlist from {source}: {err:#}");
+                    bail!("Failed to import {format:?} playlist from {source}: {err:#}");
                 }
             }
         }
@@ -229,7 +885,62 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn track_scan(pool: &SqlitePool) {
+/// Row counts of a table scan.
+#[derive(Debug, Serialize)]
+struct ScanCounts {
+    rows: u64,
+    unreadable: u64,
+}
+
+/// JSON report of the `analyze` command.
+///
+/// Counts are `null` if the corresponding check failed or the table
+/// is not available.
+#[derive(Debug, Serialize)]
+struct AnalyzeReport {
+    integrity_problems: Option<usize>,
+    foreign_key_violations: Option<usize>,
+    track: ScanCounts,
+    playlist: ScanCounts,
+    playlist_entity: ScanCounts,
+    smartlist: Option<ScanCounts>,
+    preparelist_entity: Option<ScanCounts>,
+    historylist: Option<ScanCounts>,
+    historylist_entity: Option<ScanCounts>,
+    performance_data: ScanCounts,
+    orphaned_album_art: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TrackFileIssueKind {
+    Missing,
+    Inaccessible,
+}
+
+#[derive(Debug, Serialize)]
+struct TrackFileIssueRecord {
+    track_id: i64,
+    db_path: String,
+    issue: TrackFileIssueKind,
+}
+
+/// JSON report of the `find-missing-tracks` command.
+#[derive(Debug, Serialize)]
+struct FindMissingTracksReport {
+    missing: usize,
+    inaccessible: usize,
+    issues: Vec<TrackFileIssueRecord>,
+}
+
+fn print_json(value: &impl Serialize) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, value)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+async fn track_scan(pool: &SqlitePool) -> ScanCounts {
     log::info!("Track: Scanning...");
     let (ok_count, err_count) = Track::fetch_all(pool)
         .fold((0, 0), |(ok_count, err_count), result| {
@@ -249,9 +960,69 @@ async fn track_scan(pool: &SqlitePool) {
     } else {
         log::info!("Track: Scanned {count} row(s)");
     }
+    ScanCounts {
+        rows: count,
+        unreadable: err_count,
+    }
+}
+
+async fn integrity_check(pool: &SqlitePool) -> Option<usize> {
+    log::info!("Checking integrity...");
+    match check_integrity(pool).await {
+        Ok(messages) => {
+            if messages.is_empty() {
+                log::info!("Integrity check passed");
+            } else {
+                for message in &messages {
+                    log::warn!("Integrity check: {message}");
+                }
+                log::warn!(
+                    "Integrity check found {count} problem(s)",
+                    count = messages.len()
+                );
+            }
+            Some(messages.len())
+        }
+        Err(err) => {
+            log::warn!("Failed to check integrity: {err:#}");
+            None
+        }
+    }
+}
+
+async fn foreign_key_check(pool: &SqlitePool) -> Option<usize> {
+    log::info!("Checking foreign keys...");
+    match check_foreign_keys(pool).await {
+        Ok(violations) => {
+            if violations.is_empty() {
+                log::info!("Foreign key check passed");
+            } else {
+                for violation in &violations {
+                    let ForeignKeyViolation {
+                        table,
+                        rowid,
+                        parent,
+                        fkid,
+                    } = violation;
+                    log::warn!(
+                        "Foreign key check: {table} row {rowid:?} references missing {parent} (foreign key {fkid})"
+                    );
+                }
+                log::warn!(
+                    "Foreign key check found {count} violation(s)",
+                    count = violations.len()
+                );
+            }
+            Some(violations.len())
+        }
+        Err(err) => {
+            log::warn!("Failed to check foreign keys: {err:#}");
+            None
+        }
+    }
 }
 
-async fn playlist_scan(pool: &SqlitePool) {
+async fn playlist_scan(pool: &SqlitePool) -> ScanCounts {
     log::info!("Playlist: Scanning...");
     let (ok_count, err_count) = Playlist::fetch_all(pool)
         .fold((0, 0), |(ok_count, err_count), result| {
@@ -271,9 +1042,13 @@ async fn playlist_scan(pool: &SqlitePool) {
     } else {
         log::info!("Playlist: Scanned {count} row(s)");
     }
+    ScanCounts {
+        rows: count,
+        unreadable: err_count,
+    }
 }
 
-async fn playlist_entity_scan(pool: &SqlitePool) {
+async fn playlist_entity_scan(pool: &SqlitePool) -> ScanCounts {
     log::info!("PlaylistEntity: Scanning...");
     let (ok_count, err_count) = PlaylistEntity::fetch_all(pool)
         .fold((0, 0), |(ok_count, err_count), result| {
@@ -293,12 +1068,16 @@ async fn playlist_entity_scan(pool: &SqlitePool) {
     } else {
         log::info!("PlaylistEntity: Scanned {count} row(s)");
     }
+    ScanCounts {
+        rows: count,
+        unreadable: err_count,
+    }
 }
 
-async fn smartlist_scan(pool: &SqlitePool) -> bool {
+async fn smartlist_scan(pool: &SqlitePool) -> Option<ScanCounts> {
     if !matches!(Smartlist::is_available(pool).await, Ok(true)) {
         log::info!("Smartlist: Not available in database");
-        return false;
+        return None;
     }
     log::info!("Smartlist: Scanning...");
     let (ok_count, err_count) = Smartlist::fetch_all(pool)
@@ -319,13 +1098,16 @@ async fn smartlist_scan(pool: &SqlitePool) -> bool {
     } else {
         log::info!("Smartlist: Scanned {count} row(s)");
     }
-    true
+    Some(ScanCounts {
+        rows: count,
+        unreadable: err_count,
+    })
 }
 
-async fn preparelist_entity_scan(pool: &SqlitePool) -> bool {
+async fn preparelist_entity_scan(pool: &SqlitePool) -> Option<ScanCounts> {
     if !matches!(PreparelistEntity::is_available(pool).await, Ok(true)) {
         log::info!("PreparelistEntity: Not available in database");
-        return false;
+        return None;
     }
     log::info!("PreparelistEntity: Scanning...");
     // Try to load all PreparelistEntity(s) from the database to verify the schema definition.
@@ -347,13 +1129,16 @@ async fn preparelist_entity_scan(pool: &SqlitePool) -> bool {
     } else {
         log::info!("PreparelistEntity: Scanned {count} row(s)");
     }
-    true
+    Some(ScanCounts {
+        rows: count,
+        unreadable: err_count,
+    })
 }
 
-async fn historylist_scan(pool: &SqlitePool) -> bool {
+async fn historylist_scan(pool: &SqlitePool) -> Option<ScanCounts> {
     if !matches!(Historylist::is_available(pool).await, Ok(true)) {
         log::info!("Historylist: Not available in database");
-        return false;
+        return None;
     }
     log::info!("Historylist: Scanning...");
     // Try to load all Historylist(s) from the database to verify the schema definition.
@@ -375,10 +1160,13 @@ async fn historylist_scan(pool: &SqlitePool) -> bool {
     } else {
         log::info!("Historylist: Scanned {count} row(s)");
     }
-    true
+    Some(ScanCounts {
+        rows: count,
+        unreadable: err_count,
+    })
 }
 
-async fn historylist_entity_scan(pool: &SqlitePool) {
+async fn historylist_entity_scan(pool: &SqlitePool) -> ScanCounts {
     log::info!("HistorylistEntity: Scanning...");
     // Try to load all HistorylistEntity(s) from the database to verify the schema definition.
     let (ok_count, err_count) = HistorylistEntity::fetch_all(pool)
@@ -399,9 +1187,13 @@ async fn historylist_entity_scan(pool: &SqlitePool) {
     } else {
         log::info!("HistorylistEntity: Scanned {count} rows(s)");
     }
+    ScanCounts {
+        rows: count,
+        unreadable: err_count,
+    }
 }
 
-async fn performance_data_scan(pool: &SqlitePool) {
+async fn performance_data_scan(pool: &SqlitePool) -> ScanCounts {
     log::info!("PerformanceData: Scanning...");
     // Try to load all PerformanceData from the database to verify the schema definition.
     let (ok_count, err_count) = PerformanceData::fetch_all(pool)
@@ -422,27 +1214,78 @@ async fn performance_data_scan(pool: &SqlitePool) {
     } else {
         log::info!("PerformanceData: Scanned {count} rows(s)");
     }
+    ScanCounts {
+        rows: count,
+        unreadable: err_count,
+    }
 }
 
-async fn find_track_file_issues(pool: &SqlitePool, library_path: PathBuf) {
+async fn orphaned_album_art_scan(pool: &SqlitePool) -> Option<usize> {
+    log::info!("AlbumArt: Scanning for orphaned images...");
+    match batch::find_orphaned_album_art(pool)
+        .try_collect::<Vec<_>>()
+        .await
+    {
+        Ok(album_art_ids) => {
+            if album_art_ids.is_empty() {
+                log::info!("AlbumArt: No orphaned images found");
+            } else {
+                for album_art_id in &album_art_ids {
+                    log::warn!("AlbumArt: {album_art_id} is not referenced by any track");
+                }
+                log::warn!(
+                    "AlbumArt: Found {count} orphaned image(s)",
+                    count = album_art_ids.len()
+                );
+            }
+            Some(album_art_ids.len())
+        }
+        Err(err) => {
+            log::warn!("AlbumArt: Failed to scan for orphaned images: {err:#}");
+            None
+        }
+    }
+}
+
+async fn find_track_file_issues(
+    pool: &SqlitePool,
+    library_path: PathBuf,
+    max_concurrent_checks: usize,
+) -> Vec<TrackFileIssueRecord> {
     log::info!("Track: Scanning for file issues...");
-    batch::find_track_file_issues(pool, library_path)
+    let mut progress = log_progress("Track");
+    let mut issues = Vec::new();
+    batch::find_track_file_issues(
+        pool,
+        library_path,
+        max_concurrent_checks,
+        Some(&mut progress),
+    )
         .for_each(|next_result| {
             match next_result {
-                Ok(batch::TrackFileIssueItem { db_id, db_path, file_path, file_issue }) => match file_issue {
-                    batch::TrackFileIssue::FileMissing => {
-                        log::warn!(
-                            "Track: File \"{file_path}\" of track {db_id} with path \"{db_path}\" is missing",
-                            file_path = file_path.display()
-                        );
-                    }
-                    batch::TrackFileIssue::FileError(err) => {
-                        log::warn!(
-                            "Track: File \"{file_path}\" of track {db_id} with path \"{db_path}\" is inaccessible: {err:#}",
-                            file_path = file_path.display()
-                        );
-                    }
-                },
+                Ok(batch::TrackFileIssueItem { db_id, db_path, file_path, file_issue }) => {
+                    let issue = match file_issue {
+                        batch::TrackFileIssue::FileMissing => {
+                            log::warn!(
+                                "Track: File \"{file_path}\" of track {db_id} with path \"{db_path}\" is missing",
+                                file_path = file_path.display()
+                            );
+                            TrackFileIssueKind::Missing
+                        }
+                        batch::TrackFileIssue::FileError(err) => {
+                            log::warn!(
+                                "Track: File \"{file_path}\" of track {db_id} with path \"{db_path}\" is inaccessible: {err:#}",
+                                file_path = file_path.display()
+                            );
+                            TrackFileIssueKind::Inaccessible
+                        }
+                    };
+                    issues.push(TrackFileIssueRecord {
+                        track_id: db_id.to_raw(),
+                        db_path,
+                        issue,
+                    });
+                }
                 Err(err) => {
                     // Should not occur.
                     log::error!("Database error: {err:#}");
@@ -451,6 +1294,7 @@ async fn find_track_file_issues(pool: &SqlitePool, library_path: PathBuf) {
             std::future::ready(())
         })
         .await;
+    issues
 }
 
 async fn performance_data_delete_orphaned(pool: &SqlitePool) {
@@ -504,20 +1348,61 @@ async fn album_art_delete_unused(pool: &SqlitePool) {
     }
 }
 
-async fn album_art_shrink_images(pool: &SqlitePool) {
+/// Number of processed items between progress log messages.
+const LOG_PROGRESS_INTERVAL: u64 = 1_000;
+
+fn log_progress(label: &'static str) -> impl FnMut(u64, Option<u64>) + Send {
+    move |processed, total| {
+        if !processed.is_multiple_of(LOG_PROGRESS_INTERVAL) && Some(processed) != total {
+            return;
+        }
+        if let Some(total) = total {
+            log::info!("{label}: Processed {processed} of {total} item(s)");
+        } else {
+            log::info!("{label}: Processed {processed} item(s)");
+        }
+    }
+}
+
+/// Cancels the token when receiving Ctrl-C.
+///
+/// The returned task must be aborted when the token is no longer needed.
+fn spawn_ctrl_c_handler(cancellation_token: batch::CancellationToken) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            log::warn!("Cancelling...");
+            cancellation_token.cancel();
+        }
+    })
+}
+
+async fn album_art_shrink_images(pool: &SqlitePool, options: batch::AlbumArtShrinkOptions<'_>) {
     log::info!("AlbumArt: Shrinking images...");
     {
+        let cancellation_token = batch::CancellationToken::new();
+        let ctrl_c_handler = spawn_ctrl_c_handler(cancellation_token.clone());
+        let outcome = batch::shrink_album_art_images(
+            pool,
+            options,
+            Some(&cancellation_token),
+            Some(&mut log_progress("AlbumArt")),
+        )
+        .await;
+        ctrl_c_handler.abort();
+        let is_cancelled = outcome.is_cancelled();
         let BatchOutcome {
             succeeded,
             skipped,
             failed,
             aborted_error,
-        } = batch::shrink_album_art_images(pool, endjine::AlbumArtImageQuality::Low).await;
+        } = outcome;
         log::info!(
             "AlbumArt: Shrinking of images finished: succeeded = {succeeded}, skipped = {skipped}, failed = {failed}",
             failed = failed.len()
         );
-        if let Some(err) = aborted_error {
+        if is_cancelled {
+            log::warn!("AlbumArt: Shrinking of images cancelled");
+        } else if let Some(err) = aborted_error {
             log::warn!("AlbumArt: Shrinking of images aborted with error: {err}");
         }
     }
@@ -563,6 +1448,297 @@ async fn playlist_delete_empty(pool: &SqlitePool) {
     }
 }
 
+async fn playlist_create(
+    pool: &SqlitePool,
+    playlist_path: &str,
+    is_persisted: bool,
+) -> anyhow::Result<PlaylistId> {
+    log::info!("Playlist: Creating \"{playlist_path}\"...");
+    let mut tx = pool.begin().await?;
+    let playlist_id = Playlist::create_path(&mut tx, playlist_path, is_persisted)
+        .await
+        .with_context(|| format!("create playlist \"{playlist_path}\""))?;
+    tx.commit().await?;
+    log::info!("Playlist: Created \"{playlist_path}\" with id {playlist_id}");
+    Ok(playlist_id)
+}
+
+async fn playlist_rename(
+    pool: &SqlitePool,
+    playlist_path: &str,
+    new_title: &str,
+) -> anyhow::Result<String> {
+    if !is_valid_playlist_path_segment(new_title) {
+        bail!("invalid playlist title \"{new_title}\"");
+    }
+    if playlist_path
+        .strip_suffix(PLAYLIST_PATH_SEGMENT_SEPARATOR)
+        .unwrap_or(playlist_path)
+        .is_empty()
+    {
+        bail!("cannot rename the root playlist");
+    }
+    split_playlist_path_into_segments(playlist_path)?;
+    let mut tx = pool.begin().await?;
+    let Some(id) = Playlist::find_id_by_path(&mut *tx, playlist_path).await? else {
+        bail!("playlist not found");
+    };
+    let Some(playlist) = Playlist::try_load(&mut *tx, id).await? else {
+        bail!("playlist not found");
+    };
+    if let Some(sibling_id) =
+        Playlist::find_id_by_parent_and_title(&mut *tx, playlist.parent_list_id, new_title).await?
+        && sibling_id != id
+    {
+        bail!("sibling playlist \"{new_title}\" already exists");
+    }
+    Playlist::rename(&mut *tx, id, new_title).await?;
+    let Some(PlaylistPath { path, .. }) = PlaylistPath::try_load(&mut *tx, id).await? else {
+        bail!("playlist path not found");
+    };
+    tx.commit().await?;
+    Ok(path)
+}
+
+async fn playlist_merge(
+    pool: &SqlitePool,
+    source_path: &str,
+    target_path: &str,
+    delete_source: bool,
+) -> anyhow::Result<(u64, u64)> {
+    let mut tx = pool.begin().await?;
+    let Some(source_id) = Playlist::find_id_by_path(&mut *tx, source_path).await? else {
+        bail!("source playlist not found");
+    };
+    let Some(target_id) = Playlist::find_id_by_path(&mut *tx, target_path).await? else {
+        bail!("target playlist not found");
+    };
+    let counts = Playlist::merge(&mut tx, source_id, target_id, delete_source).await?;
+    tx.commit().await?;
+    Ok(counts)
+}
+
+async fn playlist_delete(
+    pool: &SqlitePool,
+    playlist_path: &str,
+    recursive: bool,
+) -> anyhow::Result<()> {
+    log::info!("Playlist: Deleting \"{playlist_path}\"...");
+    let mut tx = pool.begin().await?;
+    let Some(playlist_id) = Playlist::find_id_by_path(&mut *tx, playlist_path)
+        .await
+        .context("find playlist by path")?
+    else {
+        bail!("playlist \"{playlist_path}\" not found");
+    };
+    let (playlist_count, entry_count) = Playlist::delete_tree(&mut tx, playlist_id, recursive)
+        .await
+        .with_context(|| format!("delete playlist \"{playlist_path}\""))?;
+    tx.commit().await?;
+    println!("Deleted {playlist_count} playlist(s) with {entry_count} entry(ies)");
+    Ok(())
+}
+
+async fn playlist_list_flat(pool: &SqlitePool) -> anyhow::Result<()> {
+    let mut playlist_paths = PlaylistPath::fetch_all(pool);
+    while let Some(PlaylistPath { path, .. }) = playlist_paths.try_next().await? {
+        println!("{path}");
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct PlaylistListItem {
+    id: i64,
+    parent_id: i64,
+    title: String,
+    path: Option<String>,
+    entry_count: u64,
+    last_edit_time: String,
+}
+
+async fn playlist_list_json(pool: &SqlitePool) -> anyhow::Result<()> {
+    let entry_counts = PlaylistEntity::count_all_lists(pool)
+        .await?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    let mut paths = PlaylistPath::fetch_all(pool)
+        .map_ok(|PlaylistPath { id, path, .. }| (id, path))
+        .try_collect::<HashMap<_, _>>()
+        .await?;
+    let items = Playlist::fetch_all(pool)
+        .map_ok(|playlist| {
+            let Playlist {
+                id,
+                title,
+                parent_list_id,
+                last_edit_time,
+                ..
+            } = playlist;
+            PlaylistListItem {
+                id: id.to_raw(),
+                parent_id: parent_list_id.to_raw(),
+                title,
+                path: paths.remove(&id.to_raw()),
+                entry_count: entry_counts.get(&id).copied().unwrap_or_default(),
+                last_edit_time: last_edit_time.to_string(),
+            }
+        })
+        .try_collect::<Vec<_>>()
+        .await?;
+    print_json(&items)
+}
+
+#[derive(Debug, Serialize)]
+struct TrackListItem {
+    id: i64,
+    artist: Option<String>,
+    title: Option<String>,
+    bpm: Option<f64>,
+    key: Option<u8>,
+    rating: Option<i64>,
+    path: Option<String>,
+}
+
+impl From<Track> for TrackListItem {
+    fn from(track: Track) -> Self {
+        let Track {
+            id,
+            artist,
+            title,
+            bpm_analyzed,
+            key,
+            rating,
+            path,
+            ..
+        } = track;
+        Self {
+            id: id.to_raw(),
+            artist,
+            title,
+            bpm: bpm_analyzed,
+            key,
+            rating,
+            path,
+        }
+    }
+}
+
+async fn track_list_tsv(pool: &SqlitePool, filter: &TrackFilter) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    let mut tracks = Track::fetch_filtered(pool, filter).map_ok(TrackListItem::from);
+    while let Some(item) = tracks.try_next().await? {
+        let TrackListItem {
+            id,
+            artist,
+            title,
+            bpm,
+            key,
+            rating,
+            path,
+        } = item;
+        writeln!(
+            stdout,
+            "{id}\t{artist}\t{title}\t{bpm}\t{key}\t{rating}\t{path}",
+            artist = artist.as_deref().unwrap_or_default(),
+            title = title.as_deref().unwrap_or_default(),
+            bpm = bpm.map(|bpm| bpm.to_string()).unwrap_or_default(),
+            key = key.map(|key| key.to_string()).unwrap_or_default(),
+            rating = rating.map(|rating| rating.to_string()).unwrap_or_default(),
+            path = path.as_deref().unwrap_or_default(),
+        )?;
+    }
+    Ok(())
+}
+
+async fn track_list_json(pool: &SqlitePool, filter: &TrackFilter) -> anyhow::Result<()> {
+    let items = Track::fetch_filtered(pool, filter)
+        .map_ok(TrackListItem::from)
+        .try_collect::<Vec<_>>()
+        .await?;
+    print_json(&items)
+}
+
+async fn playlist_list_tree(pool: &SqlitePool) -> anyhow::Result<()> {
+    let entry_counts = PlaylistEntity::count_all_lists(pool)
+        .await?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    let playlists = Playlist::fetch_all(pool).try_collect::<Vec<_>>().await?;
+    let mut children = HashMap::<_, Vec<_>>::new();
+    for playlist in &playlists {
+        children
+            .entry(playlist.parent_list_id)
+            .or_default()
+            .push(playlist);
+    }
+    print_playlist_tree(&children, &entry_counts, PlaylistId::INVALID_ZERO, 0);
+    Ok(())
+}
+
+fn print_playlist_tree(
+    children: &HashMap<PlaylistId, Vec<&Playlist>>,
+    entry_counts: &HashMap<PlaylistId, u64>,
+    parent_list_id: PlaylistId,
+    depth: usize,
+) {
+    let Some(siblings) = children.get(&parent_list_id) else {
+        return;
+    };
+    for playlist in order_playlist_siblings(siblings) {
+        let Playlist {
+            id,
+            title,
+            last_edit_time,
+            ..
+        } = playlist;
+        let entry_count = entry_counts.get(id).copied().unwrap_or_default();
+        println!(
+            "{indent}{title} (id: {id}, entries: {entry_count}, last edited: {last_edit_time})",
+            indent = "  ".repeat(depth)
+        );
+        print_playlist_tree(children, entry_counts, *id, depth + 1);
+    }
+}
+
+/// Orders siblings by following the linked list.
+///
+/// Siblings that are not reachable are appended in the order of their ids.
+fn order_playlist_siblings<'a>(siblings: &[&'a Playlist]) -> Vec<&'a Playlist> {
+    let mut remaining = siblings.to_vec();
+    let mut ordered = Vec::with_capacity(remaining.len());
+    let mut next = remaining.iter().position(|playlist| {
+        !siblings
+            .iter()
+            .any(|sibling| sibling.next_list_id == playlist.id)
+    });
+    while let Some(index) = next {
+        let playlist = remaining.swap_remove(index);
+        ordered.push(playlist);
+        next = remaining
+            .iter()
+            .position(|sibling| sibling.id == playlist.next_list_id);
+    }
+    remaining.sort_unstable_by_key(|playlist| playlist.id);
+    ordered.extend(remaining);
+    ordered
+}
+
+async fn smartlist_delete(pool: &SqlitePool, uuid: &SmartlistUuid) {
+    log::info!("Smartlist: Deleting {uuid}...");
+    match Smartlist::delete(pool, uuid).await {
+        Ok(true) => {
+            log::info!("Smartlist: Deleted {uuid}");
+        }
+        Ok(false) => {
+            log::warn!("Smartlist: {uuid} not found");
+        }
+        Err(err) => {
+            log::warn!("Smartlist: Failed to delete {uuid}: {err}");
+        }
+    }
+}
+
 fn import_track_file_paths_from_m3u_file(
     file_path: Option<&Path>,
     entry_base_path: Option<&Path>,
@@ -621,27 +1797,337 @@ fn m3u_entry_to_file_path(entry: &m3u::Entry) -> anyhow::Result<Cow<'_, Path>> {
     }
 }
 
-async fn import_playlist_from_m3u_file(
+/// Runs all consistency checks and prints the findings.
+///
+/// Returns the total number of issues.
+async fn validate(pool: &SqlitePool) -> anyhow::Result<usize> {
+    let integrity_messages = check_integrity(pool).await?;
+    for message in &integrity_messages {
+        println!("Integrity: {message}");
+    }
+    let foreign_key_violations = check_foreign_keys(pool).await?;
+    for violation in &foreign_key_violations {
+        let ForeignKeyViolation {
+            table,
+            rowid,
+            parent,
+            fkid,
+        } = violation;
+        let rowid = rowid.map(|rowid| rowid.to_string()).unwrap_or_default();
+        println!(
+            "Foreign key: {table} row {rowid} references missing {parent} (foreign key {fkid})"
+        );
+    }
+    let broken_playlist_ids = batch::find_broken_playlist_chains(pool)
+        .try_collect::<Vec<_>>()
+        .await?;
+    for playlist_id in &broken_playlist_ids {
+        println!("Playlist: broken entry chain in playlist {playlist_id}");
+    }
+    let duplicate_track_ids = batch::find_duplicate_tracks(pool)
+        .try_collect::<Vec<_>>()
+        .await?;
+    for track_ids in &duplicate_track_ids {
+        let track_ids = track_ids
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("Track: duplicate tracks {track_ids}");
+    }
+    println!(
+        "Integrity problems: {count}",
+        count = integrity_messages.len()
+    );
+    println!(
+        "Foreign key violations: {count}",
+        count = foreign_key_violations.len()
+    );
+    println!(
+        "Broken playlist chains: {count}",
+        count = broken_playlist_ids.len()
+    );
+    println!(
+        "Duplicate track groups: {count}",
+        count = duplicate_track_ids.len()
+    );
+    Ok(integrity_messages.len()
+        + foreign_key_violations.len()
+        + broken_playlist_ids.len()
+        + duplicate_track_ids.len())
+}
+
+async fn print_stats(
+    pool: &SqlitePool,
+    db_file_path: &Path,
+    info: &Information,
+) -> anyhow::Result<()> {
+    let batch::LibraryStats {
+        track_count,
+        analyzed_track_count,
+        missing_track_count,
+        playlist_count,
+        album_art_count,
+        album_art_without_image_count,
+        album_art_image_bytes,
+        performance_data_count,
+        history_session_count,
+        history_event_count,
+    } = batch::count_statistics(pool).await?;
+    let db_file_size = fs::metadata(db_file_path)
+        .context("read database file metadata")?
+        .len();
+    println!("Tracks: {track_count}");
+    println!("Analyzed tracks: {analyzed_track_count}");
+    println!("Missing tracks: {missing_track_count}");
+    println!("Playlists: {playlist_count}");
+    if let (Some(history_session_count), Some(history_event_count)) =
+        (history_session_count, history_event_count)
+    {
+        println!("History sessions: {history_session_count}");
+        println!("History events: {history_event_count}");
+    } else {
+        println!("History: not available");
+    }
+    println!("Album art: {album_art_count}");
+    println!("Album art without image: {album_art_without_image_count}");
+    println!("Album art image size: {album_art_image_bytes} byte(s)");
+    println!("Performance data: {performance_data_count}");
+    println!("Database file size: {db_file_size} byte(s)");
+    println!(
+        "Schema version: {schema_version}",
+        schema_version = info.schema_version()
+    );
+    Ok(())
+}
+
+/// Prints all groups of duplicate tracks.
+///
+/// Returns the number of duplicates, i.e. excluding the first track of each group.
+async fn find_duplicate_tracks(pool: &SqlitePool) -> anyhow::Result<u64> {
+    let groups = batch::find_duplicate_tracks(pool)
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut duplicate_count = 0;
+    for track_ids in groups {
+        let Some(first_track_id) = track_ids.first() else {
+            continue;
+        };
+        let path = Track::try_load(pool, *first_track_id)
+            .await?
+            .and_then(|track| track.path)
+            .unwrap_or_default();
+        duplicate_count += track_ids.len() as u64 - 1;
+        let track_ids = track_ids
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("{track_ids}\t{path}");
+    }
+    Ok(duplicate_count)
+}
+
+/// Asks the user for confirmation on stdin.
+fn confirm(prompt: &str) -> anyhow::Result<bool> {
+    print!("{prompt} [y/N] ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+async fn show_track(
+    pool: &SqlitePool,
+    track_id: TrackId,
+    performance_data: bool,
+    playlists: bool,
+) -> anyhow::Result<()> {
+    let Some(track) = Track::try_load(pool, track_id).await? else {
+        bail!("track {track_id} not found");
+    };
+    println!("{track:#?}");
+    if performance_data {
+        if let Some(performance_data) =
+            PerformanceData::try_load_by_track_id(pool, track_id).await?
+        {
+            let PerformanceData {
+                track_data,
+                overview_wave_form_data,
+                beat_data,
+                quick_cues,
+                loops,
+                ..
+            } = performance_data;
+            println!("Performance data:");
+            println!("  trackData: {len} byte(s)", len = track_data.len());
+            println!(
+                "  overviewWaveFormData: {len} byte(s)",
+                len = overview_wave_form_data.len()
+            );
+            println!("  beatData: {len} byte(s)", len = beat_data.len());
+            println!("  quickCues: {len} byte(s)", len = quick_cues.len());
+            println!("  loops: {len} byte(s)", len = loops.len());
+        } else {
+            println!("Performance data: none");
+        }
+    }
+    if playlists {
+        let paths = PlaylistPath::fetch_all_containing_track(pool, track_id)
+            .map_ok(|PlaylistPath { path, .. }| path)
+            .try_collect::<Vec<_>>()
+            .await?;
+        println!("Playlists: {count}", count = paths.len());
+        for path in paths {
+            println!("  {path}");
+        }
+    }
+    Ok(())
+}
+
+/// Parses an ISO 8601 calendar date, e.g. "2024-12-31".
+fn parse_iso_date(s: &str) -> anyhow::Result<Date> {
+    let mut parts = s.splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        bail!("expected date formatted as \"YYYY-MM-DD\"");
+    };
+    let month = Month::try_from(month.parse::<u8>()?)?;
+    Ok(Date::from_calendar_date(
+        year.parse()?,
+        month,
+        day.parse()?,
+    )?)
+}
+
+const fn utc_midnight_timestamp(date: Date) -> UnixTimestamp {
+    UnixTimestamp {
+        seconds_since_epoch_origin: date.midnight().assume_utc().unix_timestamp(),
+    }
+}
+
+async fn export_playlist(
+    pool: &SqlitePool,
+    library_path: &LibraryPath,
+    playlist_path: &str,
+    format: PlaylistFileFormat,
+    extended: bool,
+    output: impl Write,
+) -> anyhow::Result<()> {
+    let Some(playlist_id) = Playlist::find_id_by_path(pool, playlist_path)
+        .await
+        .context("find playlist by path")?
+    else {
+        bail!("playlist \"{playlist_path}\" not found");
+    };
+    match format {
+        PlaylistFileFormat::M3u => {
+            export_playlist_to_m3u(pool, library_path, playlist_id, extended, output).await
+        }
+        PlaylistFileFormat::Pls => {
+            let count = export_playlist_to_pls(pool, playlist_id, library_path, output).await?;
+            log::info!("Exported {count} playlist entries");
+            Ok(())
+        }
+        PlaylistFileFormat::Xspf => {
+            let count = export_playlist_to_xspf(pool, playlist_id, library_path, output).await?;
+            log::info!("Exported {count} playlist entries");
+            Ok(())
+        }
+    }
+}
+
+async fn export_playlist_to_m3u(
+    pool: &SqlitePool,
+    library_path: &LibraryPath,
+    playlist_id: PlaylistId,
+    extended: bool,
+    output: impl Write,
+) -> anyhow::Result<()> {
+    let track_ids = PlaylistEntity::load_list(pool, playlist_id)
+        .await?
+        .into_iter()
+        .map(|entry| entry.track_id)
+        .collect::<Vec<_>>();
+    let loaded_tracks = Track::find_many_by_id(&mut *pool.acquire().await?, &track_ids).await?;
+    let mut tracks = Vec::with_capacity(track_ids.len());
+    for (track_id, track) in track_ids.into_iter().zip(loaded_tracks) {
+        let Some(track) = track else {
+            log::warn!("Skipping missing track {track_id}");
+            continue;
+        };
+        let Some(file_path) = track.to_file_path(library_path) else {
+            log::warn!(
+                "Skipping track {track_id} without path",
+                track_id = track.id
+            );
+            continue;
+        };
+        tracks.push((track, file_path.to_path()));
+    }
+    if extended {
+        let mut writer = M3uWriter::new(output)?;
+        for (track, file_path) in tracks {
+            let Track {
+                length,
+                artist,
+                title,
+                ..
+            } = track;
+            let name = match (artist, title) {
+                (Some(artist), Some(title)) => format!("{artist} - {title}"),
+                (Some(name), None) | (None, Some(name)) => name,
+                (None, None) => String::new(),
+            };
+            writer.write_entry(length, &name, &file_path)?;
+        }
+        writer.finish()?;
+    } else {
+        let mut writer = m3u::Writer::new(output);
+        for (_, file_path) in tracks {
+            writer.write_entry(&m3u::path_entry(file_path))?;
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+#[expect(clippy::too_many_arguments)]
+async fn import_playlist_from_file(
     pool: &SqlitePool,
     local_db_uuid: DbUuid,
     library_path: &LibraryPath,
     playlist_path: &str,
-    mode: ImportPlaylistMode,
-    m3u_file_path: Option<&Path>,
-    m3u_base_path: Option<&Path>,
+    mode: endjine::ImportPlaylistMode,
+    format: PlaylistFileFormat,
+    file_path: Option<&Path>,
+    base_path: Option<&Path>,
 ) -> anyhow::Result<()> {
-    let m3u_base_path = m3u_base_path.or_else(|| m3u_file_path.and_then(Path::parent));
-    if let Some(m3u_base_path) = m3u_base_path {
-        log::info!(
-            "M3U base path: {m3u_base_path}",
-            m3u_base_path = m3u_base_path.display()
-        );
+    let base_path = base_path.or_else(|| file_path.and_then(Path::parent));
+    if let Some(base_path) = base_path {
+        log::info!("Base path: {base_path}", base_path = base_path.display());
     }
 
-    let track_file_paths = import_track_file_paths_from_m3u_file(m3u_file_path, m3u_base_path)
-        .context("import track file paths")?;
+    let track_file_paths = match format {
+        PlaylistFileFormat::M3u => import_track_file_paths_from_m3u_file(file_path, base_path),
+        PlaylistFileFormat::Pls => {
+            if let Some(file_path) = file_path {
+                import_pls_file(file_path, base_path)
+            } else {
+                read_pls(io::stdin().lock(), base_path)
+            }
+        }
+        PlaylistFileFormat::Xspf => {
+            if let Some(file_path) = file_path {
+                import_xspf_file(file_path, base_path)
+            } else {
+                let xml = io::read_to_string(io::stdin().lock())?;
+                read_xspf(&xml, base_path)
+            }
+        }
+    }
+    .context("import track file paths")?;
     log::info!(
-        "Imported {count} track file path(s) from M3U playlist",
+        "Imported {count} track file path(s) from {format:?} playlist",
         count = track_file_paths.len()
     );
 
@@ -661,7 +2147,7 @@ async fn import_playlist_from_track_file_paths(
     local_db_uuid: DbUuid,
     library_path: &LibraryPath,
     playlist_path: &str,
-    mode: ImportPlaylistMode,
+    mode: endjine::ImportPlaylistMode,
     track_file_paths: impl IntoIterator<Item = FilePath<'_>>,
 ) -> anyhow::Result<()> {
     let track_refs = resolve_playlist_track_refs_from_file_paths(
@@ -673,32 +2159,30 @@ async fn import_playlist_from_track_file_paths(
     .await
     .context("resolve track refs from file paths")?;
 
-    let Some(playlist_id) = Playlist::find_id_by_path(pool, playlist_path)
-        .await
-        .context("find playlist by path")?
-    else {
-        // TODO: Create new playlist.
-        bail!("playlist \"{playlist_path}\" not found");
-    };
-
     // Modify playlist within a transaction.
-    let tx = pool.begin().await?;
+    let mut tx = pool.begin().await?;
+    let (playlist_id, created) = Playlist::find_or_create_by_path(&mut tx, playlist_path, true)
+        .await
+        .context("find or create playlist by path")?;
+    if created {
+        log::info!("Created playlist \"{playlist_path}\" with id {playlist_id}");
+    }
     let ignored_track_refs = match mode {
-        ImportPlaylistMode::Append => {
+        endjine::ImportPlaylistMode::Append => {
             log::info!(
                 "Appending {track_count} track(s) to playlist \"{playlist_path}\"",
                 track_count = track_refs.len()
             );
-            Playlist::append_tracks(|| pool, playlist_id, track_refs)
+            Playlist::append_tracks(&mut tx, playlist_id, track_refs)
                 .await
                 .context("append tracks to playlist")?
         }
-        ImportPlaylistMode::Replace => {
+        endjine::ImportPlaylistMode::Replace => {
             log::info!(
                 "Replacing playlist \"{playlist_path}\" with {track_count} track(s)",
                 track_count = track_refs.len()
             );
-            Playlist::replace_tracks(|| pool, playlist_id, track_refs)
+            Playlist::replace_tracks(&mut tx, playlist_id, track_refs)
                 .await
                 .context("replace tracks of playlist")?
         }
@@ -3,9 +3,10 @@
 
 use std::io::Cursor;
 
+use anyhow::{Context as _, bail};
 use futures_util::stream::BoxStream;
-use image::{DynamicImage, ImageFormat, ImageReader, ImageResult};
-use sqlx::{FromRow, SqliteExecutor, sqlite::SqliteQueryResult};
+use image::{DynamicImage, ImageFormat, ImageReader, ImageResult, codecs::jpeg::JpegEncoder};
+use sqlx::{FromRow, SqliteExecutor, SqlitePool, sqlite::SqliteQueryResult};
 
 crate::db_id!(AlbumArtId);
 
@@ -19,6 +20,24 @@ pub struct AlbumArt {
 }
 
 impl AlbumArt {
+    /// Image formats that Engine DJ is able to read.
+    ///
+    /// Only these formats are permitted as the target of
+    /// [`Self::convert_format()`].
+    pub const SUPPORTED_TARGET_FORMATS: &[ImageFormat] = &[ImageFormat::Jpeg, ImageFormat::Png];
+
+    pub(crate) const fn new(
+        id: AlbumArtId,
+        hash: Option<String>,
+        image_data: Option<Vec<u8>>,
+    ) -> Self {
+        Self {
+            id,
+            hash,
+            image_data,
+        }
+    }
+
     #[must_use]
     pub const fn id(&self) -> AlbumArtId {
         self.id
@@ -47,6 +66,16 @@ impl AlbumArt {
         guess_image_format(image_data)
     }
 
+    /// Reads the width and height of the image.
+    ///
+    /// Only the image header is read, the pixel data is not decoded.
+    pub fn image_dimensions(&self) -> ImageResult<Option<(u32, u32)>> {
+        let Some(image_data) = self.image_data() else {
+            return Ok(None);
+        };
+        read_image_dimensions(image_data).map(Some)
+    }
+
     pub fn decode_image(&self) -> ImageResult<(Option<ImageFormat>, Option<DynamicImage>)> {
         let Some(image_data) = self.image_data() else {
             return Ok((None, None));
@@ -65,6 +94,50 @@ impl AlbumArt {
         sqlx::query_as(r#"SELECT * FROM "AlbumArt" ORDER BY "id""#).fetch(executor)
     }
 
+    /// Fetches the ids of all [`AlbumArt`] with image data larger than `min_bytes`.
+    ///
+    /// Ordered by descending image size, i.e. the largest images first.
+    #[must_use]
+    pub fn fetch_large_images<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        min_bytes: u64,
+    ) -> BoxStream<'a, sqlx::Result<AlbumArtId>> {
+        sqlx::query_scalar(
+            r#"SELECT "id" FROM "AlbumArt" WHERE LENGTH("albumArt")>?1 ORDER BY LENGTH("albumArt") DESC"#,
+        )
+        .bind(i64::try_from(min_bytes).unwrap_or(i64::MAX))
+        .fetch(executor)
+    }
+
+    /// Counts all [`AlbumArt`], including the NULL album art.
+    pub async fn count_all(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
+        let count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "AlbumArt""#)
+            .fetch_one(executor)
+            .await?;
+        debug_assert!(count >= 0);
+        Ok(count.cast_unsigned())
+    }
+
+    /// Counts all [`AlbumArt`] without image data, including the NULL album art.
+    pub async fn count_without_image(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
+        let count: i64 =
+            sqlx::query_scalar(r#"SELECT COUNT(*) FROM "AlbumArt" WHERE "albumArt" IS NULL"#)
+                .fetch_one(executor)
+                .await?;
+        debug_assert!(count >= 0);
+        Ok(count.cast_unsigned())
+    }
+
+    /// Sums up the size of all image data in bytes.
+    pub async fn total_image_bytes(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
+        let total: i64 =
+            sqlx::query_scalar(r#"SELECT COALESCE(SUM(LENGTH("albumArt")),0) FROM "AlbumArt""#)
+                .fetch_one(executor)
+                .await?;
+        debug_assert!(total >= 0);
+        Ok(total.cast_unsigned())
+    }
+
     /// Loads a single [`AlbumArt`] by id.
     ///
     /// Returns `Ok(None)` if the requested [`AlbumArt`] has not been found.
@@ -90,6 +163,46 @@ impl AlbumArt {
             .await
     }
 
+    /// Re-encodes the image of a single [`AlbumArt`] in another format.
+    ///
+    /// The `quality` in the range 1..=100 only affects lossy formats,
+    /// i.e. JPEG. Fails if the target format is not contained in
+    /// [`Self::SUPPORTED_TARGET_FORMATS`].
+    pub async fn convert_format(
+        pool: &SqlitePool,
+        id: AlbumArtId,
+        target: ImageFormat,
+        quality: u8,
+    ) -> anyhow::Result<()> {
+        if !Self::SUPPORTED_TARGET_FORMATS.contains(&target) {
+            bail!("unsupported target image format {target:?}");
+        }
+        let Some(album_art) = Self::try_load(pool, id).await? else {
+            bail!("album art {id} not found");
+        };
+        let (_, image) = album_art
+            .decode_image()
+            .with_context(|| format!("decode image data of album art {id}"))?;
+        let Some(image) = image else {
+            bail!("album art {id} has no image data");
+        };
+        let mut image_data = Vec::new();
+        if target == ImageFormat::Jpeg {
+            // JPEG does not support an alpha channel.
+            let image = DynamicImage::ImageRgb8(image.into_rgb8());
+            let encoder = JpegEncoder::new_with_quality(&mut image_data, quality);
+            image.write_with_encoder(encoder)
+        } else {
+            image.write_to(&mut Cursor::new(&mut image_data), target)
+        }
+        .with_context(|| format!("encode album art {id} as {target:?}"))?;
+        // We replace the image data but leave the original hash as is, like
+        // when shrinking album art images.
+        let result = Self::update_image(pool, id, image_data).await?;
+        debug_assert_eq!(result.rows_affected(), 1);
+        Ok(())
+    }
+
     pub async fn delete_unused(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
         let result =
             sqlx::query(r#"DELETE FROM "AlbumArt" WHERE "id" NOT IN (SELECT "albumArtId" FROM "Track" WHERE "albumArtId" IS NOT NULL)"#)
@@ -104,6 +217,11 @@ fn guess_image_format(image_data: &[u8]) -> ImageResult<Option<ImageFormat>> {
     Ok(reader.format())
 }
 
+fn read_image_dimensions(image_data: &[u8]) -> ImageResult<(u32, u32)> {
+    let reader = ImageReader::new(Cursor::new(image_data)).with_guessed_format()?;
+    reader.into_dimensions()
+}
+
 fn decode_image(image_data: &[u8]) -> ImageResult<(Option<ImageFormat>, DynamicImage)> {
     let reader = ImageReader::new(Cursor::new(image_data)).with_guessed_format()?;
     let image_format = reader.format();
@@ -115,4 +233,109 @@ pub enum AlbumArtImageQuality {
     Low,
     Medium,
     High,
+    /// JPEG quality in the range 1..=100.
+    ///
+    /// Values outside of this range are clamped.
+    Custom(u8),
+}
+
+impl AlbumArtImageQuality {
+    const LOW_JPEG_QUALITY: u8 = 50;
+    const MEDIUM_JPEG_QUALITY: u8 = 75;
+    const HIGH_JPEG_QUALITY: u8 = 90;
+
+    /// JPEG quality in the range 1..=100.
+    #[must_use]
+    pub const fn jpeg_quality(self) -> u8 {
+        match self {
+            Self::Low => Self::LOW_JPEG_QUALITY,
+            Self::Medium => Self::MEDIUM_JPEG_QUALITY,
+            Self::High => Self::HIGH_JPEG_QUALITY,
+            Self::Custom(0) => 1,
+            Self::Custom(quality @ 1..=100) => quality,
+            Self::Custom(_) => 100,
+        }
+    }
+
+    /// Maps a JPEG quality to the nearest preset.
+    ///
+    /// Use [`Self::Custom`] for the exact quality.
+    #[must_use]
+    pub const fn from_quality(quality: u8) -> Self {
+        if quality < Self::LOW_JPEG_QUALITY.midpoint(Self::MEDIUM_JPEG_QUALITY) {
+            Self::Low
+        } else if quality < Self::MEDIUM_JPEG_QUALITY.midpoint(Self::HIGH_JPEG_QUALITY) {
+            Self::Medium
+        } else {
+            Self::High
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{ImageFormat, RgbImage};
+
+    use super::{AlbumArtImageQuality, read_image_dimensions};
+
+    #[test]
+    fn image_dimensions_from_header() {
+        let mut image_data = Vec::new();
+        RgbImage::new(3, 2)
+            .write_to(&mut Cursor::new(&mut image_data), ImageFormat::Png)
+            .unwrap();
+        assert_eq!((3, 2), read_image_dimensions(&image_data).unwrap());
+        assert!(read_image_dimensions(&image_data[..8]).is_err());
+    }
+
+    #[test]
+    fn jpeg_quality_roundtrip() {
+        for quality in [
+            AlbumArtImageQuality::Low,
+            AlbumArtImageQuality::Medium,
+            AlbumArtImageQuality::High,
+        ] {
+            assert_eq!(
+                quality,
+                AlbumArtImageQuality::from_quality(quality.jpeg_quality())
+            );
+        }
+    }
+
+    #[test]
+    fn from_quality() {
+        assert_eq!(
+            AlbumArtImageQuality::Low,
+            AlbumArtImageQuality::from_quality(0)
+        );
+        assert_eq!(
+            AlbumArtImageQuality::Low,
+            AlbumArtImageQuality::from_quality(61)
+        );
+        assert_eq!(
+            AlbumArtImageQuality::Medium,
+            AlbumArtImageQuality::from_quality(62)
+        );
+        assert_eq!(
+            AlbumArtImageQuality::Medium,
+            AlbumArtImageQuality::from_quality(81)
+        );
+        assert_eq!(
+            AlbumArtImageQuality::High,
+            AlbumArtImageQuality::from_quality(82)
+        );
+        assert_eq!(
+            AlbumArtImageQuality::High,
+            AlbumArtImageQuality::from_quality(u8::MAX)
+        );
+    }
+
+    #[test]
+    fn custom_jpeg_quality_clamped() {
+        assert_eq!(1, AlbumArtImageQuality::Custom(0).jpeg_quality());
+        assert_eq!(42, AlbumArtImageQuality::Custom(42).jpeg_quality());
+        assert_eq!(100, AlbumArtImageQuality::Custom(101).jpeg_quality());
+    }
 }
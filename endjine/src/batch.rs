@@ -1,18 +1,110 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
-use std::error::Error;
+use std::{error::Error, fmt};
+
+pub use tokio_util::sync::CancellationToken;
+
+mod count_statistics;
+pub use self::count_statistics::{LibraryStats, count_statistics};
+
+mod duplicate_tracks;
+pub use self::duplicate_tracks::{delete_duplicate_tracks, find_duplicate_tracks};
+
+mod export_history_to_csv;
+pub use self::export_history_to_csv::{HistoryExportFilter, export_history_to_csv};
+
+mod find_broken_playlist_chains;
+pub use self::find_broken_playlist_chains::find_broken_playlist_chains;
+
+mod find_orphaned_album_art;
+pub use self::find_orphaned_album_art::find_orphaned_album_art;
 
 mod find_track_file_issues;
 pub use self::find_track_file_issues::{
-    TrackFileIssue, TrackFileIssueItem, find_track_file_issues,
+    DEFAULT_MAX_CONCURRENT_TRACK_FILE_CHECKS, TrackFileIssue, TrackFileIssueItem,
+    find_track_file_issues,
 };
 
+mod find_tracks_with_changed_files;
+pub use self::find_tracks_with_changed_files::find_tracks_with_changed_files;
+
+mod import_playlists_from_directory;
+pub use self::import_playlists_from_directory::import_playlists_from_directory;
+
+mod import_track_metadata_from_files;
+pub use self::import_track_metadata_from_files::import_track_metadata_from_files;
+
+mod merge_history_sessions;
+pub use self::merge_history_sessions::merge_history_sessions;
+
+mod normalize_track_paths;
+pub use self::normalize_track_paths::normalize_track_paths;
+
 mod purge_album_art;
 pub use self::purge_album_art::purge_album_art;
 
+mod rebase_track_paths;
+pub use self::rebase_track_paths::{RebasedTrackPath, rebase_track_paths};
+
+mod rebuild_origin_references;
+pub use self::rebuild_origin_references::rebuild_origin_references;
+
+mod remove_orphaned_playlist_entries;
+pub use self::remove_orphaned_playlist_entries::remove_orphaned_playlist_entries;
+
 mod shrink_album_art_images;
-pub use self::shrink_album_art_images::shrink_album_art_images;
+pub use self::shrink_album_art_images::{AlbumArtShrinkOptions, shrink_album_art_images};
+
+mod sync_track_availability;
+pub use self::sync_track_availability::sync_track_availability;
+
+/// Receives progress updates from batch operations.
+///
+/// Implemented for all closures with a matching signature.
+pub trait BatchProgress: Send {
+    /// Reports the number of items that have been processed so far.
+    ///
+    /// The total number of items is `None` if unknown.
+    fn on_progress(&mut self, processed: u64, total: Option<u64>);
+}
+
+impl<F> BatchProgress for F
+where
+    F: FnMut(u64, Option<u64>) + Send,
+{
+    fn on_progress(&mut self, processed: u64, total: Option<u64>) {
+        self(processed, total);
+    }
+}
+
+fn report_progress(
+    progress: &mut Option<&mut dyn BatchProgress>,
+    processed: u64,
+    total: Option<u64>,
+) {
+    if let Some(progress) = progress.as_deref_mut() {
+        progress.on_progress(processed, total);
+    }
+}
+
+fn is_cancelled(cancellation_token: Option<&CancellationToken>) -> bool {
+    cancellation_token.is_some_and(CancellationToken::is_cancelled)
+}
+
+/// A batch operation has been cancelled.
+///
+/// Sentinel for [`BatchOutcome::aborted_error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchCancelled;
+
+impl fmt::Display for BatchCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("cancelled")
+    }
+}
+
+impl Error for BatchCancelled {}
 
 #[derive(Debug, Default)]
 pub struct BatchOutcome {
@@ -32,6 +124,14 @@ pub struct BatchOutcome {
 }
 
 impl BatchOutcome {
+    /// Checks if the batch operation has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.aborted_error
+            .as_deref()
+            .is_some_and(<dyn Error>::is::<BatchCancelled>)
+    }
+
     #[must_use]
     pub(crate) fn abort(self, error: Box<dyn Error>) -> Self {
         debug_assert!(self.aborted_error.is_none());
@@ -40,4 +140,10 @@ impl BatchOutcome {
             ..self
         }
     }
+
+    #[must_use]
+    pub(crate) fn cancel(self) -> Self {
+        log::info!("Cancelled batch operation");
+        self.abort(Box::new(BatchCancelled))
+    }
 }
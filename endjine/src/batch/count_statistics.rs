@@ -0,0 +1,86 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use sqlx::SqlitePool;
+
+use crate::{AlbumArt, Historylist};
+
+/// Summary of the library contents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryStats {
+    pub track_count: u64,
+    pub analyzed_track_count: u64,
+    /// Tracks with a file path that are flagged as unavailable.
+    ///
+    /// The flag is only updated when synchronizing the availability.
+    pub missing_track_count: u64,
+    pub playlist_count: u64,
+    pub album_art_count: u64,
+    /// Album art without image data, including the NULL album art.
+    pub album_art_without_image_count: u64,
+    /// Total size of all album art image data in bytes.
+    pub album_art_image_bytes: u64,
+    pub performance_data_count: u64,
+    /// `None` if the database contains no history tables.
+    pub history_session_count: Option<u64>,
+    /// `None` if the database contains no history tables.
+    pub history_event_count: Option<u64>,
+}
+
+/// Counts the contents of the library.
+///
+/// All counts are obtained from a single snapshot of the database.
+pub async fn count_statistics(pool: &SqlitePool) -> sqlx::Result<LibraryStats> {
+    let mut tx = pool.begin().await?;
+    let (
+        track_count,
+        analyzed_track_count,
+        missing_track_count,
+        playlist_count,
+        album_art_count,
+        performance_data_count,
+    ) = sqlx::query_as::<_, (i64, i64, i64, i64, i64, i64)>(
+        r#"SELECT
+           (SELECT COUNT(*) FROM "Track"),
+           (SELECT COUNT(*) FROM "Track" WHERE "isAnalyzed"),
+           (SELECT COUNT(*) FROM "Track" WHERE "path" IS NOT NULL AND NOT "isAvailable"),
+           (SELECT COUNT(*) FROM "Playlist"),
+           (SELECT COUNT(*) FROM "AlbumArt"),
+           (SELECT COUNT(*) FROM "PerformanceData")"#,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    let album_art_without_image_count = AlbumArt::count_without_image(&mut *tx).await?;
+    let album_art_image_bytes = AlbumArt::total_image_bytes(&mut *tx).await?;
+    let (history_session_count, history_event_count) =
+        if Historylist::is_available(&mut *tx).await? {
+            let (session_count, event_count) = sqlx::query_as::<_, (i64, i64)>(
+                r#"SELECT
+               (SELECT COUNT(*) FROM "Historylist"),
+               (SELECT COUNT(*) FROM "HistorylistEntity")"#,
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            (Some(session_count), Some(event_count))
+        } else {
+            (None, None)
+        };
+    tx.commit().await?;
+    Ok(LibraryStats {
+        track_count: to_count(track_count),
+        analyzed_track_count: to_count(analyzed_track_count),
+        missing_track_count: to_count(missing_track_count),
+        playlist_count: to_count(playlist_count),
+        album_art_count: to_count(album_art_count),
+        album_art_without_image_count,
+        album_art_image_bytes,
+        performance_data_count: to_count(performance_data_count),
+        history_session_count: history_session_count.map(to_count),
+        history_event_count: history_event_count.map(to_count),
+    })
+}
+
+const fn to_count(count: i64) -> u64 {
+    debug_assert!(count >= 0);
+    count.cast_unsigned()
+}
@@ -0,0 +1,83 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use futures_util::{StreamExt as _, TryStreamExt as _, stream::BoxStream};
+use sqlx::{SqliteExecutor, SqlitePool, types::Json};
+
+use crate::{PlaylistEntity, PlaylistEntityId, TrackId};
+
+/// Finds tracks that share the same file path.
+///
+/// Each item contains the ids of all tracks with the same path,
+/// ordered by id.
+#[must_use]
+pub fn find_duplicate_tracks<'a>(
+    executor: impl SqliteExecutor<'a> + 'a,
+) -> BoxStream<'a, sqlx::Result<Vec<TrackId>>> {
+    sqlx::query_scalar::<_, Json<Vec<i64>>>(
+        r#"SELECT json_group_array("id") FROM
+           (SELECT "id","path" FROM "Track" WHERE "path" IS NOT NULL ORDER BY "id")
+           GROUP BY "path" HAVING COUNT(*)>1"#,
+    )
+    .fetch(executor)
+    .map_ok(|Json(ids)| {
+        let mut ids = ids.into_iter().map(TrackId::new).collect::<Vec<_>>();
+        // The order of aggregated values is not guaranteed.
+        ids.sort_unstable();
+        ids
+    })
+    .boxed()
+}
+
+/// Deletes tracks that share the same file path.
+///
+/// Keeps the track with the lowest id. References from playlist entries
+/// and performance data are moved to the kept track before deleting
+/// the duplicates. Playlist entries that would become duplicates
+/// are deleted.
+///
+/// Returns the number of deleted tracks.
+pub async fn delete_duplicate_tracks(pool: &SqlitePool) -> sqlx::Result<u64> {
+    let duplicate_groups = find_duplicate_tracks(pool).try_collect::<Vec<_>>().await?;
+    let mut deleted_count = 0;
+    let mut tx = pool.begin().await?;
+    for ids in duplicate_groups {
+        let Some((&keep_id, delete_ids)) = ids.split_first() else {
+            continue;
+        };
+        for &delete_id in delete_ids {
+            debug_assert!(keep_id < delete_id);
+            sqlx::query(r#"UPDATE OR IGNORE "PlaylistEntity" SET "trackId"=?1 WHERE "trackId"=?2"#)
+                .bind(keep_id)
+                .bind(delete_id)
+                .execute(&mut *tx)
+                .await?;
+            // Remaining entries would become duplicates within their playlist.
+            let entity_ids = sqlx::query_scalar::<_, PlaylistEntityId>(
+                r#"SELECT "id" FROM "PlaylistEntity" WHERE "trackId"=?1"#,
+            )
+            .bind(delete_id)
+            .fetch_all(&mut *tx)
+            .await?;
+            for entity_id in entity_ids {
+                PlaylistEntity::delete_and_unlink(&mut tx, entity_id).await?;
+            }
+            // Remaining performance data is deleted by cascade.
+            sqlx::query(
+                r#"UPDATE OR IGNORE "PerformanceData" SET "trackId"=?1 WHERE "trackId"=?2"#,
+            )
+            .bind(keep_id)
+            .bind(delete_id)
+            .execute(&mut *tx)
+            .await?;
+            let result = sqlx::query(r#"DELETE FROM "Track" WHERE "id"=?1"#)
+                .bind(delete_id)
+                .execute(&mut *tx)
+                .await?;
+            log::info!("Deleted track {delete_id} as duplicate of track {keep_id}");
+            deleted_count += result.rows_affected();
+        }
+    }
+    tx.commit().await?;
+    Ok(deleted_count)
+}
@@ -0,0 +1,158 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::io::Write;
+
+use anyhow::Context as _;
+use futures_util::TryStreamExt as _;
+use sqlx::{SqlitePool, types::time::OffsetDateTime};
+
+use crate::{LibraryPath, UnixTimestamp};
+
+const CSV_HEADER: [&str; 6] = [
+    "session_id",
+    "session_title",
+    "start_time_utc",
+    "artist",
+    "title",
+    "path",
+];
+
+/// Selects the history entries for exporting.
+///
+/// All criteria are optional and combined with AND.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryExportFilter {
+    /// Inclusive lower bound of the start time.
+    pub from: Option<UnixTimestamp>,
+    /// Exclusive upper bound of the start time.
+    pub to: Option<UnixTimestamp>,
+    pub session_id: Option<i64>,
+}
+
+/// Exports played tracks as CSV.
+///
+/// Writes a header row followed by one row per history entry that
+/// matches the filter, ordered by start time. Track file paths are
+/// resolved relative to the library path.
+///
+/// Returns the number of rows written, excluding the header row.
+pub async fn export_history_to_csv(
+    pool: &SqlitePool,
+    library_path: &LibraryPath,
+    filter: &HistoryExportFilter,
+    mut output: impl Write,
+) -> anyhow::Result<u64> {
+    let HistoryExportFilter {
+        from,
+        to,
+        session_id,
+    } = filter;
+    write_csv_record(&mut output, CSV_HEADER)?;
+    let mut rows = sqlx::query_as::<
+        _,
+        (
+            i64,
+            Option<String>,
+            UnixTimestamp,
+            Option<String>,
+            Option<String>,
+            Option<String>,
+        ),
+    >(
+        r#"SELECT "Historylist"."sessionId","Historylist"."title",
+           "HistorylistEntity"."startTime",
+           "Track"."artist","Track"."title","Track"."path"
+           FROM "HistorylistEntity"
+           JOIN "Historylist" ON "Historylist"."id"="HistorylistEntity"."listId"
+           LEFT JOIN "Track" ON "Track"."id"="HistorylistEntity"."trackId"
+           WHERE (?1 IS NULL OR "HistorylistEntity"."startTime">=?1)
+           AND (?2 IS NULL OR "HistorylistEntity"."startTime"<?2)
+           AND (?3 IS NULL OR "Historylist"."sessionId"=?3)
+           ORDER BY "HistorylistEntity"."startTime","HistorylistEntity"."id""#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(session_id)
+    .fetch(pool);
+    let mut row_count = 0;
+    while let Some((session_id, session_title, start_time, artist, title, path)) =
+        rows.try_next().await?
+    {
+        let start_time = OffsetDateTime::from_unix_timestamp(start_time.seconds_since_epoch_origin)
+            .with_context(|| format!("invalid start time {start_time:?}"))?;
+        let start_time = format_utc_date_time(start_time);
+        let path = path
+            .map(|path| {
+                let mut file_path = library_path.file_path().clone();
+                file_path.append_relative_suffix(&path);
+                file_path.to_string()
+            })
+            .unwrap_or_default();
+        write_csv_record(
+            &mut output,
+            [
+                session_id.to_string().as_str(),
+                session_title.as_deref().unwrap_or_default(),
+                start_time.as_str(),
+                artist.as_deref().unwrap_or_default(),
+                title.as_deref().unwrap_or_default(),
+                path.as_str(),
+            ],
+        )?;
+        row_count += 1;
+    }
+    output.flush()?;
+    Ok(row_count)
+}
+
+/// Formats a UTC date/time as RFC 3339 with second precision.
+fn format_utc_date_time(date_time: OffsetDateTime) -> String {
+    debug_assert!(date_time.offset().is_utc());
+    let (year, month, day) = date_time.to_calendar_date();
+    let (hour, minute, second) = date_time.to_hms();
+    format!(
+        "{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z",
+        month = u8::from(month)
+    )
+}
+
+fn write_csv_record<'a>(
+    output: &mut impl Write,
+    fields: impl IntoIterator<Item = &'a str>,
+) -> std::io::Result<()> {
+    for (index, field) in fields.into_iter().enumerate() {
+        if index > 0 {
+            output.write_all(b",")?;
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(output, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            output.write_all(field.as_bytes())?;
+        }
+    }
+    output.write_all(b"\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::types::time::OffsetDateTime;
+
+    use super::{format_utc_date_time, write_csv_record};
+
+    #[test]
+    fn format_utc_date_time_rfc3339() {
+        let date_time = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        assert_eq!(format_utc_date_time(date_time), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn write_csv_record_quoted() {
+        let mut output = Vec::new();
+        write_csv_record(&mut output, ["1", "a,b", "say \"hi\"", "", "line\nbreak"]).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "1,\"a,b\",\"say \"\"hi\"\"\",,\"line\nbreak\"\r\n"
+        );
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use futures_util::stream::BoxStream;
+use sqlx::SqliteExecutor;
+
+use crate::PlaylistId;
+
+/// Finds playlists with a broken linked list of entries.
+///
+/// The linked list is considered as broken if `nextEntityId` references
+/// a non-existent entry or an entry of a different playlist, or if the
+/// order of the linked list does not match the order of `membershipReference`.
+///
+/// Ordered by playlist id.
+#[must_use]
+pub fn find_broken_playlist_chains<'a>(
+    executor: impl SqliteExecutor<'a> + 'a,
+) -> BoxStream<'a, sqlx::Result<PlaylistId>> {
+    sqlx::query_scalar(
+        r#"SELECT DISTINCT "entry"."listId"
+           FROM "PlaylistEntity" AS "entry"
+           LEFT JOIN "PlaylistEntity" AS "next" ON "next"."id"="entry"."nextEntityId"
+           WHERE
+           -- The last entry must not be followed by other entries.
+           ("entry"."nextEntityId"=0 AND EXISTS (
+               SELECT 1 FROM "PlaylistEntity" AS "other"
+               WHERE "other"."listId"="entry"."listId"
+               AND "other"."id"<>"entry"."id"
+               AND "other"."membershipReference">="entry"."membershipReference"))
+           -- The next entry must exist in the same playlist and follow the entry.
+           OR ("entry"."nextEntityId"<>0 AND (
+               "next"."id" IS NULL
+               OR "next"."listId"<>"entry"."listId"
+               OR "next"."membershipReference"<="entry"."membershipReference"))
+           -- No other entry must be located between the entry and the next entry.
+           OR ("entry"."nextEntityId"<>0 AND EXISTS (
+               SELECT 1 FROM "PlaylistEntity" AS "other"
+               WHERE "other"."listId"="entry"."listId"
+               AND "other"."membershipReference">"entry"."membershipReference"
+               AND "other"."membershipReference"<"next"."membershipReference"))
+           ORDER BY "entry"."listId""#,
+    )
+    .fetch(executor)
+}
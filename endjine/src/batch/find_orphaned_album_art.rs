@@ -0,0 +1,26 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use futures_util::stream::BoxStream;
+use sqlx::SqliteExecutor;
+
+use crate::AlbumArtId;
+
+/// Finds album art that is not referenced by any track.
+///
+/// Read-only counterpart of [`AlbumArt::delete_unused`](crate::AlbumArt::delete_unused)
+/// for auditing. The default album art with id 1 is never reported.
+///
+/// Ordered by id.
+#[must_use]
+pub fn find_orphaned_album_art<'a>(
+    executor: impl SqliteExecutor<'a> + 'a,
+) -> BoxStream<'a, sqlx::Result<AlbumArtId>> {
+    sqlx::query_scalar(
+        r#"SELECT "id" FROM "AlbumArt"
+           WHERE "id"<>1
+           AND "id" NOT IN (SELECT "albumArtId" FROM "Track" WHERE "albumArtId" IS NOT NULL)
+           ORDER BY "id""#,
+    )
+    .fetch(executor)
+}
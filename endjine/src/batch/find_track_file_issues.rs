@@ -1,13 +1,15 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
-use std::{future, io, path::PathBuf};
+use std::{future, io, path::PathBuf, sync::Arc};
 
-use futures_util::{StreamExt as _, stream::BoxStream};
+use futures_util::{FutureExt as _, StreamExt as _, stream::BoxStream};
 use sqlx::SqliteExecutor;
-use tokio::task::block_in_place;
+use tokio::{sync::Semaphore, task::spawn_blocking};
 
-use crate::TrackId;
+use crate::{BatchProgress, TrackId};
+
+use super::report_progress;
 
 #[derive(Debug)]
 pub enum TrackFileIssue {
@@ -27,49 +29,93 @@ pub struct TrackFileIssueItem {
     pub file_issue: TrackFileIssue,
 }
 
+/// Default limit for the number of concurrent file checks.
+pub const DEFAULT_MAX_CONCURRENT_TRACK_FILE_CHECKS: usize = 8;
+
 /// Finds track file issues.
 ///
 /// Track file paths in the database are relative to the path of the
 /// database file.
+///
+/// Files are checked on the blocking thread pool with at most
+/// `max_concurrent_checks` checks in flight, e.g. to avoid saturating
+/// USB drives with high seek latency. A value of 0 is treated as 1.
+/// Results are reported in the order of the tracks in the database.
+///
+/// The total number of tracks is not reported as progress.
 #[must_use]
 pub fn find_track_file_issues<'a>(
     executor: impl SqliteExecutor<'a> + 'a,
     library_path: PathBuf,
+    max_concurrent_checks: usize,
+    mut progress: Option<&'a mut dyn BatchProgress>,
 ) -> BoxStream<'a, sqlx::Result<TrackFileIssueItem>> {
+    let max_concurrent_checks = max_concurrent_checks.max(1);
+    let semaphore = Arc::new(Semaphore::new(max_concurrent_checks));
+    let mut processed = 0;
     sqlx::query_as::<_, (TrackId, String)>(
         r#"SELECT "id","path" FROM "Track" WHERE "path" IS NOT NULL"#,
     )
     .fetch(executor)
-    .filter_map(move |next_result| {
+    .map(move |next_result| {
         let (db_id, db_path) = match next_result {
             Ok(ok) => ok,
             Err(err) => {
                 // Pass all errors through.
-                return future::ready(Some(Err(err)));
+                return future::ready(Some(Err(err))).left_future();
             }
         };
-        log::debug!("Checking path \"{db_path}\" of track {db_id}");
-        let mut file_path = library_path.join(&db_path);
-        let file_issue = block_in_place(||
-                // Blocking file I/O operations.
-                match check_file_exists(&mut file_path) {
-                    Ok(true) => None,
-                    Ok(false) => Some(TrackFileIssue::FileMissing),
-                    Err(err) => Some(TrackFileIssue::FileError(err)),
-                });
-        future::ready(file_issue.map(|file_issue| {
-            Ok(TrackFileIssueItem {
+        let file_path = library_path.join(&db_path);
+        let semaphore = Arc::clone(&semaphore);
+        async move {
+            // The semaphore is never closed, i.e. acquiring a permit never fails.
+            let _permit = semaphore.acquire_owned().await.ok();
+            log::debug!("Checking path \"{db_path}\" of track {db_id}");
+            let (file_path, file_issue) = check_file_issue(file_path).await;
+            Some(Ok((db_id, db_path, file_path, file_issue)))
+        }
+        .right_future()
+    })
+    // Prefetch rows from the database while waiting for pending checks.
+    .buffered(max_concurrent_checks.saturating_mul(2))
+    .filter_map(move |next_result| {
+        let item = next_result.map(|next_result| {
+            let (db_id, db_path, file_path, file_issue) = next_result?;
+            processed += 1;
+            report_progress(&mut progress, processed, None);
+            Ok(file_issue.map(|file_issue| TrackFileIssueItem {
                 db_id,
                 db_path,
                 file_path,
                 file_issue,
-            })
-        }))
+            }))
+        });
+        future::ready(item.and_then(Result::transpose))
     })
     .boxed()
 }
 
-fn check_file_exists(file_path: &mut PathBuf) -> io::Result<bool> {
+async fn check_file_issue(mut file_path: PathBuf) -> (PathBuf, Option<TrackFileIssue>) {
+    let fallback_file_path = file_path.clone();
+    spawn_blocking(move || {
+        // Blocking file I/O operations.
+        let file_issue = match check_file_exists(&mut file_path) {
+            Ok(true) => None,
+            Ok(false) => Some(TrackFileIssue::FileMissing),
+            Err(err) => Some(TrackFileIssue::FileError(err)),
+        };
+        (file_path, file_issue)
+    })
+    .await
+    .unwrap_or_else(|err| {
+        (
+            fallback_file_path,
+            Some(TrackFileIssue::FileError(io::Error::other(err))),
+        )
+    })
+}
+
+pub(super) fn check_file_exists(file_path: &mut PathBuf) -> io::Result<bool> {
     if let (Some(parent_path), Some(file_name)) = (file_path.parent(), file_path.file_name()) {
         let parent_path = parent_path.canonicalize()?;
         *file_path = parent_path.join(file_name);
@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::{fs, future, path::PathBuf};
+
+use futures_util::{StreamExt as _, stream::BoxStream};
+use sqlx::{SqlitePool, types::time::OffsetDateTime};
+use tokio::task::block_in_place;
+
+use crate::{LibraryPath, TrackId, UnixTimestamp};
+
+/// Finds tracks with files that have been modified after the last edit.
+///
+/// Compares the modification time of the file with the `lastEditTime`
+/// of the track, both truncated to whole seconds. Yields the ID and the
+/// absolute file path of all tracks with a newer file.
+///
+/// Files that could not be accessed are logged and skipped.
+#[must_use]
+pub fn find_tracks_with_changed_files<'a>(
+    pool: &'a SqlitePool,
+    library_path: &LibraryPath,
+) -> BoxStream<'a, sqlx::Result<(TrackId, PathBuf)>> {
+    let library_path = library_path.to_path();
+    sqlx::query_as::<_, (TrackId, String, UnixTimestamp)>(
+        r#"SELECT "id","path","lastEditTime" FROM "Track" WHERE "path" IS NOT NULL ORDER BY "id""#,
+    )
+    .fetch(pool)
+    .filter_map(move |next_result| {
+        let (id, db_path, last_edit_time) = match next_result {
+            Ok(ok) => ok,
+            Err(err) => {
+                // Pass all errors through.
+                return future::ready(Some(Err(err)));
+            }
+        };
+        let file_path = library_path.join(&db_path);
+        // Blocking file I/O operation.
+        let modified = block_in_place(|| fs::metadata(&file_path)?.modified());
+        let modified = match modified {
+            Ok(modified) => UnixTimestamp::from_datetime(OffsetDateTime::from(modified)),
+            Err(err) => {
+                log::warn!(
+                    "Failed to read modification time of file \"{file_path}\" of track {id}: {err}",
+                    file_path = file_path.display()
+                );
+                return future::ready(None);
+            }
+        };
+        if modified <= last_edit_time {
+            return future::ready(None);
+        }
+        log::debug!(
+            "File \"{file_path}\" of track {id} has been modified after the last edit",
+            file_path = file_path.display()
+        );
+        future::ready(Some(Ok((id, file_path))))
+    })
+    .boxed()
+}
@@ -0,0 +1,193 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, anyhow};
+use sqlx::SqlitePool;
+use tokio::task::block_in_place;
+
+use crate::{
+    BatchOutcome, DbUuid, ImportPlaylistMode, LibraryPath, Playlist,
+    concat_playlist_path_segments_to_string, import_m3u_file, is_valid_playlist_path_segment,
+    resolve_playlist_track_refs_from_file_paths, split_playlist_path_into_segments,
+};
+
+/// Imports all M3U files from a directory as playlists.
+///
+/// The directory is walked recursively. Each subdirectory becomes a
+/// playlist folder and each `.m3u` or `.m3u8` file becomes a playlist
+/// named after the file stem, nested below `root_playlist_path`. Missing
+/// playlists are created. An empty `root_playlist_path` imports the
+/// hierarchy at the top level.
+///
+/// Each file is imported within a separate transaction. Files that fail
+/// to import are counted as failed.
+///
+/// Fails if the directory could not be read.
+pub async fn import_playlists_from_directory(
+    pool: &SqlitePool,
+    local_db_uuid: DbUuid,
+    library_path: &LibraryPath,
+    dir: &Path,
+    root_playlist_path: &str,
+    mode: ImportPlaylistMode,
+) -> anyhow::Result<BatchOutcome> {
+    let root_segments = if root_playlist_path.is_empty() {
+        Vec::new()
+    } else {
+        split_playlist_path_into_segments(root_playlist_path)?
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect()
+    };
+    let mut playlist_files = Vec::new();
+    block_in_place(|| {
+        // Blocking file I/O operations.
+        collect_playlist_files(dir, &root_segments, &mut playlist_files)
+    })
+    .with_context(|| format!("read directory \"{dir}\"", dir = dir.display()))?;
+    let mut outcome = BatchOutcome::default();
+    for (file_path, segments) in playlist_files {
+        if !segments
+            .iter()
+            .all(|segment| is_valid_playlist_path_segment(segment))
+        {
+            log::warn!(
+                "Skipping file \"{file_path}\" with invalid playlist path",
+                file_path = file_path.display()
+            );
+            outcome.failed.push(
+                anyhow!(
+                    "invalid playlist path for file \"{file_path}\"",
+                    file_path = file_path.display()
+                )
+                .into(),
+            );
+            continue;
+        }
+        let playlist_path = concat_playlist_path_segments_to_string(&segments);
+        match import_playlist_file(
+            pool,
+            local_db_uuid,
+            library_path,
+            &file_path,
+            &playlist_path,
+            mode,
+        )
+        .await
+        {
+            Ok(ignored_count) => {
+                if ignored_count > 0 {
+                    log::warn!(
+                        "Ignored {ignored_count} duplicate track(s) in playlist \"{playlist_path}\""
+                    );
+                }
+                log::info!(
+                    "Imported playlist \"{playlist_path}\" from file \"{file_path}\"",
+                    file_path = file_path.display()
+                );
+                outcome.succeeded += 1;
+            }
+            Err(err) => {
+                log::warn!(
+                    "Failed to import playlist \"{playlist_path}\" from file \"{file_path}\": {err:#}",
+                    file_path = file_path.display()
+                );
+                outcome.failed.push(err.into());
+            }
+        }
+    }
+    Ok(outcome)
+}
+
+/// Collects all M3U files and their playlist path segments.
+///
+/// Entries are visited in lexicographical order.
+fn collect_playlist_files(
+    dir: &Path,
+    segments: &[String],
+    playlist_files: &mut Vec<(PathBuf, Vec<String>)>,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_unstable_by_key(fs::DirEntry::file_name);
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            let mut segments = segments.to_vec();
+            segments.push(entry.file_name().to_string_lossy().into_owned());
+            collect_playlist_files(&path, &segments, playlist_files)?;
+            continue;
+        }
+        if !is_m3u_file_path(&path) {
+            continue;
+        }
+        let Some(file_stem) = path.file_stem() else {
+            continue;
+        };
+        let mut segments = segments.to_vec();
+        segments.push(file_stem.to_string_lossy().into_owned());
+        playlist_files.push((path, segments));
+    }
+    Ok(())
+}
+
+fn is_m3u_file_path(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        extension.eq_ignore_ascii_case("m3u") || extension.eq_ignore_ascii_case("m3u8")
+    })
+}
+
+/// Imports a single M3U file.
+///
+/// Returns the number of ignored duplicate tracks.
+async fn import_playlist_file(
+    pool: &SqlitePool,
+    local_db_uuid: DbUuid,
+    library_path: &LibraryPath,
+    file_path: &Path,
+    playlist_path: &str,
+    mode: ImportPlaylistMode,
+) -> anyhow::Result<usize> {
+    let track_file_paths = block_in_place(|| import_m3u_file(file_path, None))?;
+    let track_refs = resolve_playlist_track_refs_from_file_paths(
+        pool,
+        local_db_uuid,
+        library_path,
+        track_file_paths,
+    )
+    .await
+    .context("resolve track refs from file paths")?;
+    let mut tx = pool.begin().await?;
+    let (playlist_id, _created) = Playlist::find_or_create_by_path(&mut tx, playlist_path, true)
+        .await
+        .context("find or create playlist by path")?;
+    let ignored_track_refs = match mode {
+        ImportPlaylistMode::Append => Playlist::append_tracks(&mut tx, playlist_id, track_refs)
+            .await
+            .context("append tracks to playlist")?,
+        ImportPlaylistMode::Replace => Playlist::replace_tracks(&mut tx, playlist_id, track_refs)
+            .await
+            .context("replace tracks of playlist")?,
+    };
+    tx.commit().await?;
+    Ok(ignored_track_refs.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::is_m3u_file_path;
+
+    #[test]
+    fn m3u_file_extensions() {
+        assert!(is_m3u_file_path(Path::new("a.m3u")));
+        assert!(is_m3u_file_path(Path::new("a.M3U8")));
+        assert!(!is_m3u_file_path(Path::new("a.pls")));
+        assert!(!is_m3u_file_path(Path::new("m3u")));
+    }
+}
@@ -0,0 +1,141 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::path::Path;
+
+use lofty::{
+    error::FileParseError,
+    file::TaggedFileExt as _,
+    tag::{Accessor as _, ItemKey, Tag},
+};
+use sqlx::SqlitePool;
+use tokio::task::block_in_place;
+
+use crate::{BatchOutcome, BatchProgress, LibraryPath, Track, TrackId, TrackMetadata};
+
+use super::{CancellationToken, is_cancelled, report_progress};
+
+const BATCH_SIZE: u16 = 128;
+
+/// Imports tag metadata from track files.
+///
+/// Reads the tags of all track files that have not been imported yet and
+/// updates the metadata of the corresponding tracks. Tracks without any
+/// tags are skipped.
+///
+/// Cancellation is checked after each track.
+pub async fn import_track_metadata_from_files(
+    pool: &SqlitePool,
+    library_path: &LibraryPath,
+    cancellation_token: Option<&CancellationToken>,
+    mut progress: Option<&mut dyn BatchProgress>,
+) -> BatchOutcome {
+    let library_path = library_path.to_path();
+    let mut outcome = BatchOutcome::default();
+    let total = if progress.is_some() {
+        match sqlx::query_scalar::<_, i64>(
+            r#"SELECT COUNT(*) FROM "Track" WHERE "path" IS NOT NULL AND NOT "isMetadataImported""#,
+        )
+        .fetch_one(pool)
+        .await
+        {
+            Ok(count) => {
+                debug_assert!(count >= 0);
+                Some(count.cast_unsigned())
+            }
+            Err(fetch_error) => {
+                log::warn!("Failed to count rows: {fetch_error}");
+                return outcome.abort(Box::new(fetch_error));
+            }
+        }
+    } else {
+        None
+    };
+    let mut processed = 0;
+    // All ids in the database are strictly positive.
+    let mut last_id = TrackId::INVALID_MIN_EXCLUSIVE;
+    loop {
+        let rows = match sqlx::query_as::<_, (TrackId, String)>(
+            r#"SELECT "id","path" FROM "Track"
+            WHERE "id">?1 AND "path" IS NOT NULL AND NOT "isMetadataImported"
+            ORDER BY "id" LIMIT ?2"#,
+        )
+        .bind(last_id)
+        .bind(BATCH_SIZE)
+        .fetch_all(pool)
+        .await
+        {
+            Ok(rows) => rows,
+            Err(fetch_error) => {
+                log::warn!("Failed to fetch rows: {fetch_error}");
+                return outcome.abort(Box::new(fetch_error));
+            }
+        };
+        let Some((id, _)) = rows.last() else {
+            return outcome;
+        };
+        debug_assert!(*id > last_id);
+        last_id = *id;
+        for (id, db_path) in rows {
+            if is_cancelled(cancellation_token) {
+                return outcome.cancel();
+            }
+            processed += 1;
+            report_progress(&mut progress, processed, total);
+            let file_path = library_path.join(&db_path);
+            let metadata = match block_in_place(|| read_track_metadata(&file_path)) {
+                Ok(Some(metadata)) => metadata,
+                Ok(None) => {
+                    log::debug!("Skipping track {id} without tags");
+                    outcome.skipped += 1;
+                    continue;
+                }
+                Err(err) => {
+                    log::warn!(
+                        "Failed to read tags from file \"{file_path}\" of track {id}: {err}",
+                        file_path = file_path.display()
+                    );
+                    outcome.failed.push(Box::new(err));
+                    continue;
+                }
+            };
+            match Track::update_metadata(pool, id, &metadata).await {
+                Ok(updated) => {
+                    debug_assert!(updated);
+                    log::info!("Imported metadata of track {id}");
+                    outcome.succeeded += 1;
+                }
+                Err(err) => {
+                    log::warn!("Failed to update metadata of track {id}: {err}");
+                    outcome.failed.push(Box::new(err));
+                }
+            }
+        }
+    }
+}
+
+fn read_track_metadata(file_path: &Path) -> Result<Option<TrackMetadata>, FileParseError> {
+    let tagged_file = lofty::read_from_path(file_path)?;
+    let Some(tag) = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag())
+    else {
+        return Ok(None);
+    };
+    Ok(Some(track_metadata_from_tag(tag)))
+}
+
+fn track_metadata_from_tag(tag: &Tag) -> TrackMetadata {
+    let get_string = |key| tag.get_string(key).map(ToOwned::to_owned);
+    TrackMetadata {
+        title: tag.title().map(Into::into),
+        artist: tag.artist().map(Into::into),
+        album: tag.album().map(Into::into),
+        genre: tag.genre().map(Into::into),
+        comment: tag.comment().map(Into::into),
+        label: get_string(ItemKey::Label),
+        composer: get_string(ItemKey::Composer),
+        remixer: get_string(ItemKey::Remixer),
+        year: tag.date().map(|date| date.year.into()),
+    }
+}
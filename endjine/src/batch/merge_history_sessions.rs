@@ -0,0 +1,66 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{Historylist, HistorylistEntity, HistorylistId};
+
+/// Merges fragmented history sessions into a single session.
+///
+/// Moves all entries of the source sessions into the target session and
+/// deletes the source sessions afterwards. Entries of the target session
+/// that share both track and start time are deduplicated, keeping the
+/// oldest entry. The target session is ignored if it is contained in
+/// the source sessions.
+///
+/// Fails with [`sqlx::Error::RowNotFound`] if the target session does
+/// not exist.
+///
+/// Returns the number of entries that have been moved into the target
+/// session, excluding duplicates.
+pub async fn merge_history_sessions(
+    pool: &SqlitePool,
+    source_ids: &[HistorylistId],
+    target_id: HistorylistId,
+) -> sqlx::Result<u64> {
+    let mut tx = pool.begin().await?;
+    if Historylist::try_load(&mut *tx, target_id).await?.is_none() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    // Duplicates that already exist in the target session must not be
+    // subtracted from the moved entries.
+    delete_duplicate_entries(&mut tx, target_id).await?;
+    let target_count_before = HistorylistEntity::count_list(&mut *tx, target_id).await?;
+    for &source_id in source_ids {
+        if source_id == target_id {
+            continue;
+        }
+        sqlx::query(r#"UPDATE "HistorylistEntity" SET "listId"=?1 WHERE "listId"=?2"#)
+            .bind(target_id)
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await?;
+        Historylist::delete(&mut tx, source_id).await?;
+    }
+    delete_duplicate_entries(&mut tx, target_id).await?;
+    let target_count_after = HistorylistEntity::count_list(&mut *tx, target_id).await?;
+    tx.commit().await?;
+    debug_assert!(target_count_after >= target_count_before);
+    Ok(target_count_after - target_count_before)
+}
+
+/// Deletes entries that share both track and start time, keeping the oldest entry.
+async fn delete_duplicate_entries(
+    connection: &mut SqliteConnection,
+    list_id: HistorylistId,
+) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        r#"DELETE FROM "HistorylistEntity" WHERE "listId"=?1 AND "id" NOT IN (
+             SELECT MIN("id") FROM "HistorylistEntity" WHERE "listId"=?1
+             GROUP BY "trackId","startTime")"#,
+    )
+    .bind(list_id)
+    .execute(connection)
+    .await?;
+    Ok(result.rows_affected())
+}
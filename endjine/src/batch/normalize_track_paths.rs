@@ -0,0 +1,92 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use futures_util::TryStreamExt as _;
+use sqlx::SqlitePool;
+
+use crate::TrackId;
+
+/// Normalizes the separators and components of track paths.
+///
+/// Backslashes are replaced with forward slashes. Repeated slashes and
+/// `.` components are removed. `..` components are preserved.
+///
+/// Returns the number of updated tracks.
+pub async fn normalize_track_paths(pool: &SqlitePool) -> sqlx::Result<u64> {
+    let mut tx = pool.begin().await?;
+    let normalized_paths = sqlx::query_as::<_, (TrackId, String)>(
+        r#"SELECT "id","path" FROM "Track"
+           WHERE instr("path",'\')>0 OR instr("path",'//')>0
+           OR "path" LIKE '%/./%' OR "path" LIKE './%' OR "path" LIKE '%/.'
+           ORDER BY "id""#,
+    )
+    .fetch(&mut *tx)
+    .try_filter_map(|(track_id, path)| async move {
+        Ok(normalize_path(&path).map(|normalized_path| (track_id, normalized_path)))
+    })
+    .try_collect::<Vec<_>>()
+    .await?;
+    let mut updated_count = 0;
+    for (track_id, path) in normalized_paths {
+        let result = sqlx::query(r#"UPDATE "Track" SET "path"=?2 WHERE "id"=?1"#)
+            .bind(track_id)
+            .bind(&path)
+            .execute(&mut *tx)
+            .await?;
+        debug_assert_eq!(result.rows_affected(), 1);
+        log::debug!("Normalized path of track {track_id}: {path}");
+        updated_count += result.rows_affected();
+    }
+    tx.commit().await?;
+    Ok(updated_count)
+}
+
+/// Normalizes a path.
+///
+/// Returns `None` if the path is already normalized.
+#[must_use]
+fn normalize_path(path: &str) -> Option<String> {
+    let path_with_slashes = path.replace('\\', "/");
+    let mut normalized_path = String::with_capacity(path_with_slashes.len());
+    if path_with_slashes.starts_with('/') {
+        normalized_path.push('/');
+    }
+    let mut segments = path_with_slashes
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".");
+    if let Some(first_segment) = segments.next() {
+        normalized_path.push_str(first_segment);
+        for segment in segments {
+            normalized_path.push('/');
+            normalized_path.push_str(segment);
+        }
+    }
+    (normalized_path != path).then_some(normalized_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_path;
+
+    #[test]
+    fn normalize_path_separators_and_components() {
+        assert_eq!(None, normalize_path("../Music/Artist/track.mp3"));
+        assert_eq!(None, normalize_path("/Volumes/Music/track.mp3"));
+        assert_eq!(
+            Some("../Music/Artist/track.mp3"),
+            normalize_path(r"..\Music\Artist\track.mp3").as_deref()
+        );
+        assert_eq!(
+            Some("../Music/Artist/track.mp3"),
+            normalize_path("./..//Music/./Artist/track.mp3").as_deref()
+        );
+        assert_eq!(
+            Some("/Volumes/Music/track.mp3"),
+            normalize_path("//Volumes/Music/./track.mp3").as_deref()
+        );
+        assert_eq!(
+            Some("../../Music/track.mp3"),
+            normalize_path(r"..\../Music\\track.mp3").as_deref()
+        );
+    }
+}
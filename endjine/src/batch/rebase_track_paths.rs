@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use anyhow::bail;
+use futures_util::TryStreamExt as _;
+use relative_path::{RelativePath, RelativePathBuf};
+use sqlx::SqlitePool;
+
+use crate::{TrackId, track::RELATIVE_TRACK_PATH_PREFIX};
+
+/// A track path that has been rebased.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebasedTrackPath {
+    pub track_id: TrackId,
+    pub old_path: String,
+    pub new_path: String,
+}
+
+/// Replaces the prefix of track paths.
+///
+/// Both prefixes must be relative to the library directory, i.e. start
+/// with `..`. Prefixes only match whole path segments.
+///
+/// If `dry_run` is `true` the database is not modified.
+///
+/// Returns all rebased track paths.
+pub async fn rebase_track_paths(
+    pool: &SqlitePool,
+    old_prefix: &RelativePath,
+    new_prefix: &RelativePath,
+    dry_run: bool,
+) -> anyhow::Result<Vec<RebasedTrackPath>> {
+    for prefix in [old_prefix, new_prefix] {
+        if !prefix.starts_with(RELATIVE_TRACK_PATH_PREFIX) {
+            bail!("path prefix \"{prefix}\" must start with \"{RELATIVE_TRACK_PATH_PREFIX}\"");
+        }
+    }
+    let mut tx = pool.begin().await?;
+    let rebased_paths =
+        sqlx::query_as::<_, (TrackId, String)>(
+            r#"SELECT "id","path" FROM "Track"
+           WHERE substr("path",1,length(?1))=?1
+           ORDER BY "id""#,
+        )
+        .bind(old_prefix.as_str())
+        .fetch(&mut *tx)
+        .try_filter_map(|(track_id, old_path)| {
+            let rebased_path = rebase_path(RelativePath::new(&old_path), old_prefix, new_prefix)
+                .map(|new_path| RebasedTrackPath {
+                    track_id,
+                    new_path: new_path.into_string(),
+                    old_path,
+                });
+            async move { Ok(rebased_path) }
+        })
+        .try_collect::<Vec<_>>()
+        .await?;
+    if dry_run {
+        return Ok(rebased_paths);
+    }
+    for RebasedTrackPath {
+        track_id, new_path, ..
+    } in &rebased_paths
+    {
+        let result = sqlx::query(r#"UPDATE "Track" SET "path"=?2 WHERE "id"=?1"#)
+            .bind(track_id)
+            .bind(new_path)
+            .execute(&mut *tx)
+            .await?;
+        debug_assert_eq!(result.rows_affected(), 1);
+        log::debug!("Rebased path of track {track_id}: {new_path}");
+    }
+    tx.commit().await?;
+    Ok(rebased_paths)
+}
+
+#[must_use]
+fn rebase_path(
+    path: &RelativePath,
+    old_prefix: &RelativePath,
+    new_prefix: &RelativePath,
+) -> Option<RelativePathBuf> {
+    let suffix = path.strip_prefix(old_prefix).ok()?;
+    Some(new_prefix.join(suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use relative_path::RelativePath;
+
+    use super::rebase_path;
+
+    #[test]
+    fn rebase_path_matches_whole_segments() {
+        let old_prefix = RelativePath::new("../Music");
+        let new_prefix = RelativePath::new("../../Volumes/Music");
+        assert_eq!(
+            Some("../../Volumes/Music/Artist/track.mp3"),
+            rebase_path(
+                RelativePath::new("../Music/Artist/track.mp3"),
+                old_prefix,
+                new_prefix
+            )
+            .as_deref()
+            .map(RelativePath::as_str)
+        );
+        assert_eq!(
+            None,
+            rebase_path(
+                RelativePath::new("../Music2/track.mp3"),
+                old_prefix,
+                new_prefix
+            )
+        );
+        assert_eq!(
+            None,
+            rebase_path(
+                RelativePath::new("../Other/Music/track.mp3"),
+                old_prefix,
+                new_prefix
+            )
+        );
+    }
+}
@@ -0,0 +1,33 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use sqlx::SqlitePool;
+
+use crate::DbUuid;
+
+/// Repairs tracks with missing origin references.
+///
+/// Tracks with a NULL, empty, zero, or nil origin database UUID are
+/// considered as originating from the local database and reference
+/// themselves afterwards. The same applies to local tracks with a
+/// missing origin track id.
+///
+/// Returns the number of corrected tracks.
+pub async fn rebuild_origin_references(
+    pool: &SqlitePool,
+    local_db_uuid: DbUuid,
+) -> sqlx::Result<u64> {
+    debug_assert!(!local_db_uuid.is_nil());
+    let result = sqlx::query(
+        r#"UPDATE "Track"
+           SET "originDatabaseUuid"=?1, "originTrackId"="id"
+           WHERE "originDatabaseUuid" IS NULL
+           OR "originDatabaseUuid" IN ('','0',?2)
+           OR ("originDatabaseUuid"=?1 AND ("originTrackId" IS NULL OR "originTrackId"=0))"#,
+    )
+    .bind(local_db_uuid)
+    .bind(DbUuid::nil().as_uuid().hyphenated().to_string())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,29 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use sqlx::SqlitePool;
+
+use crate::{PlaylistEntity, PlaylistEntityId};
+
+/// Removes all playlist entries that reference non-existent tracks.
+///
+/// The linked lists of the affected playlists are repaired by relinking
+/// the preceding entries.
+///
+/// Returns the number of deleted entries.
+pub async fn remove_orphaned_playlist_entries(pool: &SqlitePool) -> sqlx::Result<u64> {
+    let mut tx = pool.begin().await?;
+    let ids = sqlx::query_scalar::<_, PlaylistEntityId>(
+        r#"SELECT "id" FROM "PlaylistEntity" WHERE "trackId" NOT IN (SELECT "id" FROM "Track")"#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let mut deleted_count = 0;
+    for id in ids {
+        if PlaylistEntity::delete_and_unlink(&mut tx, id).await? {
+            deleted_count += 1;
+        }
+    }
+    tx.commit().await?;
+    Ok(deleted_count)
+}
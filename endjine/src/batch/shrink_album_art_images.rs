@@ -1,16 +1,70 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
-use futures_util::StreamExt;
-use image::{ImageFormat, codecs::jpeg::JpegEncoder};
+use futures_util::{StreamExt as _, TryStreamExt as _, stream};
+use image::{ImageFormat, codecs::jpeg::JpegEncoder, imageops::FilterType};
 use sqlx::SqlitePool;
 use tokio::task::block_in_place;
 
-use crate::{AlbumArt, AlbumArtId, AlbumArtImageQuality, BatchOutcome};
+use crate::{AlbumArt, AlbumArtId, AlbumArtImageQuality, BatchOutcome, BatchProgress};
+
+use super::{CancellationToken, is_cancelled, report_progress};
 
 const BATCH_UPDATE_SIZE: u16 = 128;
 
-const MAX_RATIO: f64 = 0.75;
+/// Image formats that are converted by default.
+const DEFAULT_TARGET_FORMATS: &[ImageFormat] =
+    &[ImageFormat::Png, ImageFormat::Bmp, ImageFormat::Tga];
+
+/// Options for [`shrink_album_art_images()`].
+#[derive(Debug, Clone, Copy)]
+pub struct AlbumArtShrinkOptions<'a> {
+    /// Quality of the re-encoded JPEG images in the range 1..=100.
+    pub jpeg_quality: u8,
+
+    /// Maximum ratio between the new and the old size.
+    ///
+    /// Re-encoded images that do not save enough space are discarded.
+    pub max_ratio: f64,
+
+    /// Maximum width and height of images.
+    ///
+    /// Larger images are resized before re-encoding while preserving
+    /// their aspect ratio. This also applies to images with formats that
+    /// are not included in `target_formats`. The dimensions are read from
+    /// the image header before decoding the whole image.
+    pub max_dimension: Option<u32>,
+
+    /// Minimum size of images in bytes.
+    ///
+    /// Only images that are larger are considered as candidates. They are
+    /// selected in advance and processed in order of descending size.
+    pub min_image_size: Option<u64>,
+
+    /// Image formats that should be converted.
+    pub target_formats: &'a [ImageFormat],
+}
+
+impl AlbumArtShrinkOptions<'static> {
+    pub const DEFAULT_MAX_RATIO: f64 = 0.75;
+
+    #[must_use]
+    pub const fn new(image_quality: AlbumArtImageQuality) -> Self {
+        Self {
+            jpeg_quality: image_quality.jpeg_quality(),
+            max_ratio: Self::DEFAULT_MAX_RATIO,
+            max_dimension: None,
+            min_image_size: None,
+            target_formats: DEFAULT_TARGET_FORMATS,
+        }
+    }
+}
+
+impl Default for AlbumArtShrinkOptions<'static> {
+    fn default() -> Self {
+        Self::new(AlbumArtImageQuality::Medium)
+    }
+}
 
 #[derive(Debug)]
 struct BatchUpdateItem {
@@ -20,23 +74,58 @@ struct BatchUpdateItem {
     image_data: Vec<u8>,
 }
 
-#[must_use]
-const fn jpeg_quality(image_quality: AlbumArtImageQuality) -> u8 {
-    match image_quality {
-        AlbumArtImageQuality::Low => 50,
-        AlbumArtImageQuality::Medium => 75,
-        AlbumArtImageQuality::High => 90,
-    }
-}
-
 #[expect(clippy::too_many_lines, reason = "TODO")]
 pub async fn shrink_album_art_images(
     pool: &SqlitePool,
-    image_quality: AlbumArtImageQuality,
+    options: AlbumArtShrinkOptions<'_>,
+    cancellation_token: Option<&CancellationToken>,
+    mut progress: Option<&mut dyn BatchProgress>,
 ) -> BatchOutcome {
+    let AlbumArtShrinkOptions {
+        jpeg_quality,
+        max_ratio,
+        max_dimension,
+        min_image_size,
+        target_formats,
+    } = options;
+    let exceeds_max_dimension = |(width, height): (u32, u32)| {
+        max_dimension.is_some_and(|max_dimension| width > max_dimension || height > max_dimension)
+    };
     let mut outcome = BatchOutcome::default();
+    let candidate_ids = if let Some(min_image_size) = min_image_size {
+        match AlbumArt::fetch_large_images(pool, min_image_size)
+            .try_collect::<Vec<_>>()
+            .await
+        {
+            Ok(candidate_ids) => Some(candidate_ids),
+            Err(fetch_error) => {
+                log::warn!("Failed to fetch candidates: {fetch_error}");
+                return outcome.abort(Box::new(fetch_error));
+            }
+        }
+    } else {
+        None
+    };
+    let total = if progress.is_some() {
+        if let Some(candidate_ids) = &candidate_ids {
+            Some(candidate_ids.len() as u64)
+        } else {
+            match AlbumArt::count_all(pool).await {
+                Ok(count) => Some(count),
+                Err(fetch_error) => {
+                    log::warn!("Failed to count rows: {fetch_error}");
+                    return outcome.abort(Box::new(fetch_error));
+                }
+            }
+        }
+    } else {
+        None
+    };
+    let mut processed = 0;
+    let mut cancelled = false;
     // All ids in the database are strictly positive.
     let mut last_id = AlbumArtId::INVALID_MIN_EXCLUSIVE;
+    let mut next_candidate_index = 0;
     let mut batch_update_items: Vec<BatchUpdateItem> = Vec::with_capacity(BATCH_UPDATE_SIZE.into());
     loop {
         if !batch_update_items.is_empty() {
@@ -70,18 +159,54 @@ pub async fn shrink_album_art_images(
             }
             debug_assert!(batch_update_items.is_empty());
         }
-        let mut rows = sqlx::query_as(r#"SELECT * FROM "AlbumArt" WHERE "id">?1 ORDER BY "id""#)
-            .bind(last_id)
-            .fetch(pool);
+        if cancelled {
+            // Pending updates have been stored before.
+            return outcome.cancel();
+        }
+        let mut rows = if let Some(candidate_ids) = &candidate_ids {
+            stream::iter(&candidate_ids[next_candidate_index..])
+                .then(|&id| AlbumArt::try_load(pool, id))
+                .boxed()
+        } else {
+            sqlx::query_as(r#"SELECT * FROM "AlbumArt" WHERE "id">?1 ORDER BY "id""#)
+                .bind(last_id)
+                .fetch(pool)
+                .map_ok(Some)
+                .boxed()
+        };
         let mut row_fetch_count = 0;
         while let Some(row) = rows.next().await {
+            if is_cancelled(cancellation_token) {
+                cancelled = true;
+                break;
+            }
             row_fetch_count += 1;
+            processed += 1;
+            report_progress(&mut progress, processed, total);
             let (id, format, image, old_size) = match row {
-                Ok(row) => {
-                    let album_art: AlbumArt = row;
+                Ok(None) => {
+                    log::debug!("Skipping deleted album art");
+                    outcome.skipped += 1;
+                    continue;
+                }
+                Ok(Some(album_art)) => {
                     let id = album_art.id();
-                    debug_assert!(id > last_id);
-                    last_id = id;
+                    if candidate_ids.is_none() {
+                        debug_assert!(id > last_id);
+                        last_id = id;
+                    }
+                    // Check the image format and dimensions before decoding the whole image.
+                    if let Ok(Some(format)) = album_art.guess_image_format()
+                        && !target_formats.contains(&format)
+                        && !matches!(
+                            album_art.image_dimensions(),
+                            Ok(Some(dimensions)) if exceeds_max_dimension(dimensions)
+                        )
+                    {
+                        log::debug!("Skipping album art {id} with image format {format:?}");
+                        outcome.skipped += 1;
+                        continue;
+                    }
                     match block_in_place(|| album_art.decode_image()) {
                         Ok((_, None)) => {
                             log::debug!("Skipping missing album art {id}");
@@ -97,28 +222,19 @@ pub async fn shrink_album_art_images(
                         }
                         Ok((Some(format), Some(image))) => {
                             debug_assert!(album_art.hash().is_some());
-                            match format {
-                                format @ (ImageFormat::Png
-                                | ImageFormat::Bmp
-                                | ImageFormat::Tga) => (
-                                    id,
-                                    format,
-                                    image,
-                                    album_art.image_data().map_or(0, <[u8]>::len),
-                                ),
-                                ImageFormat::Jpeg => {
-                                    log::debug!("Skipping album art {id} with JPEG image format");
-                                    outcome.skipped += 1;
-                                    continue;
-                                }
-                                unsupported_format => {
-                                    log::info!(
-                                        "Skipping album art {id} with unsupported image format {unsupported_format:?}"
-                                    );
-                                    outcome.skipped += 1;
-                                    continue;
-                                }
+                            if !target_formats.contains(&format)
+                                && !exceeds_max_dimension((image.width(), image.height()))
+                            {
+                                log::debug!("Skipping album art {id} with image format {format:?}");
+                                outcome.skipped += 1;
+                                continue;
                             }
+                            (
+                                id,
+                                format,
+                                image,
+                                album_art.image_data().map_or(0, <[u8]>::len),
+                            )
                         }
                         Err(err) => {
                             log::warn!("Failed to decode image data of album art {id}: {err}");
@@ -135,9 +251,16 @@ pub async fn shrink_album_art_images(
             // We replace the image data but leave the original hash as is. This ensures
             // that Engine DJ will reuse album art when adding tracks with the same
             // image.
+            let image = match max_dimension {
+                Some(max_dimension) if exceeds_max_dimension((image.width(), image.height())) => {
+                    block_in_place(|| {
+                        image.resize(max_dimension, max_dimension, FilterType::Lanczos3)
+                    })
+                }
+                _ => image,
+            };
             let mut image_data_jpeg = Vec::with_capacity(256_000);
-            let encoder =
-                JpegEncoder::new_with_quality(&mut image_data_jpeg, jpeg_quality(image_quality));
+            let encoder = JpegEncoder::new_with_quality(&mut image_data_jpeg, jpeg_quality);
             if let Err(err) = block_in_place(|| image.write_with_encoder(encoder)) {
                 log::warn!("Failed to re-encode album art {id} as JPEG: {err}");
                 outcome.failed.push(Box::new(err));
@@ -147,7 +270,7 @@ pub async fn shrink_album_art_images(
             if new_size < old_size && new_size > 0 {
                 #[expect(clippy::cast_precision_loss)]
                 let ratio = new_size as f64 / old_size as f64;
-                if ratio <= MAX_RATIO {
+                if ratio <= max_ratio {
                     debug_assert!(batch_update_items.len() < BATCH_UPDATE_SIZE.into());
                     batch_update_items.push(BatchUpdateItem {
                         id,
@@ -165,7 +288,8 @@ pub async fn shrink_album_art_images(
             log::info!("Keeping album art {id}: old size = {old_size}, new size = {new_size}");
             outcome.skipped += 1;
         }
-        if row_fetch_count > 0 {
+        next_candidate_index += row_fetch_count;
+        if row_fetch_count > 0 || cancelled {
             continue;
         }
         debug_assert!(batch_update_items.is_empty());
@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use sqlx::SqlitePool;
+use tokio::task::block_in_place;
+
+use crate::{BatchOutcome, BatchProgress, LibraryPath, TrackId};
+
+use super::{
+    CancellationToken, find_track_file_issues::check_file_exists, is_cancelled, report_progress,
+};
+
+const BATCH_UPDATE_SIZE: u16 = 128;
+
+/// Updates the availability of all tracks with a file path.
+///
+/// Checks if the track files exist and updates the `isAvailable` flag
+/// of the corresponding tracks accordingly.
+///
+/// Tracks with an unchanged availability are counted as skipped.
+///
+/// Cancellation is checked after each track. Pending updates of tracks
+/// that have already been checked are stored before returning.
+pub async fn sync_track_availability(
+    pool: &SqlitePool,
+    library_path: &LibraryPath,
+    cancellation_token: Option<&CancellationToken>,
+    mut progress: Option<&mut dyn BatchProgress>,
+) -> anyhow::Result<BatchOutcome> {
+    let library_path = library_path.to_path();
+    let total = if progress.is_some() {
+        let count: i64 =
+            sqlx::query_scalar(r#"SELECT COUNT(*) FROM "Track" WHERE "path" IS NOT NULL"#)
+                .fetch_one(pool)
+                .await?;
+        debug_assert!(count >= 0);
+        Some(count.cast_unsigned())
+    } else {
+        None
+    };
+    let mut processed = 0;
+    let mut outcome = BatchOutcome::default();
+    // All ids in the database are strictly positive.
+    let mut last_id = TrackId::INVALID_MIN_EXCLUSIVE;
+    loop {
+        let rows = sqlx::query_as::<_, (TrackId, String, bool)>(
+            r#"SELECT "id","path","isAvailable" FROM "Track"
+            WHERE "id">?1 AND "path" IS NOT NULL
+            ORDER BY "id" LIMIT ?2"#,
+        )
+        .bind(last_id)
+        .bind(BATCH_UPDATE_SIZE)
+        .fetch_all(pool)
+        .await?;
+        let Some((id, _, _)) = rows.last() else {
+            return Ok(outcome);
+        };
+        debug_assert!(*id > last_id);
+        last_id = *id;
+        let mut batch_update_items = Vec::with_capacity(rows.len());
+        let mut cancelled = false;
+        block_in_place(|| {
+            // Blocking file I/O operations.
+            for (id, db_path, was_available) in rows {
+                if is_cancelled(cancellation_token) {
+                    cancelled = true;
+                    break;
+                }
+                processed += 1;
+                let mut file_path = library_path.join(&db_path);
+                match check_file_exists(&mut file_path) {
+                    Ok(is_available) => {
+                        if is_available == was_available {
+                            outcome.skipped += 1;
+                        } else {
+                            batch_update_items.push((id, is_available));
+                        }
+                    }
+                    Err(err) => {
+                        log::warn!(
+                            "Failed to check file \"{file_path}\" of track {id}: {err}",
+                            file_path = file_path.display()
+                        );
+                        outcome.failed.push(Box::new(err));
+                    }
+                }
+            }
+        });
+        if batch_update_items.is_empty() {
+            report_progress(&mut progress, processed, total);
+            if cancelled {
+                return Ok(outcome.cancel());
+            }
+            continue;
+        }
+        log::debug!(
+            "Updating availability of {batch_size} track(s)",
+            batch_size = batch_update_items.len()
+        );
+        let mut tx = pool.begin().await?;
+        for (id, is_available) in batch_update_items {
+            match sqlx::query(r#"UPDATE "Track" SET "isAvailable"=?1 WHERE "id"=?2"#)
+                .bind(is_available)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+            {
+                Ok(result) => {
+                    debug_assert_eq!(result.rows_affected(), 1);
+                    log::info!("Updated availability of track {id}: {is_available}");
+                    outcome.succeeded += 1;
+                }
+                Err(err) => {
+                    log::warn!("Failed to update availability of track {id}: {err}");
+                    outcome.failed.push(Box::new(err));
+                }
+            }
+        }
+        tx.commit().await?;
+        report_progress(&mut progress, processed, total);
+        if cancelled {
+            return Ok(outcome.cancel());
+        }
+    }
+}
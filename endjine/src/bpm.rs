@@ -0,0 +1,119 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::fmt;
+
+use sqlx::{
+    Decode, Encode, Sqlite,
+    encode::IsNull,
+    error::BoxDynError,
+    sqlite::{SqliteArgumentValue, SqliteTypeInfo, SqliteValueRef},
+};
+
+/// Tempo in beats per minute.
+///
+/// Always finite and positive.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct Bpm(f64);
+
+impl Bpm {
+    /// Creates a tempo from a floating-point value.
+    ///
+    /// Returns `None` if the value is not finite or not positive.
+    #[must_use]
+    pub const fn try_new(value: f64) -> Option<Self> {
+        if value.is_finite() && value > 0.0 {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub const fn as_f64(self) -> f64 {
+        self.0
+    }
+
+    /// Half the tempo.
+    #[must_use]
+    pub const fn half(self) -> Self {
+        Self(self.0 / 2.0)
+    }
+
+    /// Double the tempo.
+    #[must_use]
+    pub const fn double(self) -> Self {
+        Self(self.0 * 2.0)
+    }
+
+    /// Scales the tempo by a factor, e.g. for pitch adjustments.
+    ///
+    /// Returns `None` if the resulting tempo is invalid.
+    #[must_use]
+    pub const fn scaled(self, factor: f64) -> Option<Self> {
+        Self::try_new(self.0 * factor)
+    }
+
+    /// Relative deviation from another tempo in percent.
+    ///
+    /// Positive if `self` is faster than `other`.
+    #[must_use]
+    pub const fn percent_diff(self, other: Self) -> f64 {
+        (self.0 / other.0 - 1.0) * 100.0
+    }
+}
+
+impl fmt::Display for Bpm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl sqlx::Type<Sqlite> for Bpm {
+    fn type_info() -> SqliteTypeInfo {
+        <f64 as sqlx::Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <f64 as sqlx::Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for Bpm {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let value = <f64 as Decode<'r, Sqlite>>::decode(value)?;
+        Self::try_new(value).ok_or_else(|| format!("invalid BPM {value}").into())
+    }
+}
+
+impl<'q> Encode<'q, Sqlite> for Bpm {
+    fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> Result<IsNull, BoxDynError> {
+        <f64 as Encode<Sqlite>>::encode_by_ref(&self.0, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bpm;
+
+    #[test]
+    fn try_new() {
+        assert_eq!(Some(124.0), Bpm::try_new(124.0).map(Bpm::as_f64));
+        assert!(Bpm::try_new(0.0).is_none());
+        assert!(Bpm::try_new(-0.0).is_none());
+        assert!(Bpm::try_new(-1.0).is_none());
+        assert!(Bpm::try_new(f64::NAN).is_none());
+        assert!(Bpm::try_new(f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let bpm = Bpm::try_new(120.0).unwrap();
+        assert_eq!(Bpm::try_new(60.0), Some(bpm.half()));
+        assert_eq!(Bpm::try_new(240.0), Some(bpm.double()));
+        assert!((bpm.scaled(1.05).unwrap().as_f64() - 126.0).abs() < 1e-9);
+        assert!(bpm.scaled(0.0).is_none());
+        assert!((bpm.double().percent_diff(bpm) - 100.0).abs() < f64::EPSILON);
+    }
+}
@@ -1,7 +1,8 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
-use sqlx::FromRow;
+use futures_util::stream::BoxStream;
+use sqlx::{FromRow, SqliteExecutor};
 
 use crate::TrackId;
 
@@ -13,3 +14,42 @@ pub struct ChangeLog {
     pub id: ChangeLogId,
     pub track_id: TrackId,
 }
+
+impl ChangeLog {
+    /// Fetches all [`ChangeLog`]s asynchronously.
+    ///
+    /// Ordered by ID, i.e. chronologically.
+    #[must_use]
+    pub fn fetch_all<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "ChangeLog" ORDER BY "id""#).fetch(executor)
+    }
+
+    /// Records a change of a [`Track`](crate::Track).
+    pub async fn insert_for_track(
+        executor: impl SqliteExecutor<'_>,
+        track_id: TrackId,
+    ) -> sqlx::Result<ChangeLogId> {
+        let result = sqlx::query(r#"INSERT INTO "ChangeLog" ("trackId") VALUES (?1)"#)
+            .bind(track_id)
+            .execute(executor)
+            .await?;
+        debug_assert_eq!(result.rows_affected(), 1);
+        Ok(ChangeLogId::new(result.last_insert_rowid()))
+    }
+
+    /// Deletes all recorded changes of a [`Track`](crate::Track).
+    ///
+    /// Returns the number of deleted entries.
+    pub async fn delete_by_track(
+        executor: impl SqliteExecutor<'_>,
+        track_id: TrackId,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query(r#"DELETE FROM "ChangeLog" WHERE "trackId"=?1"#)
+            .bind(track_id)
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
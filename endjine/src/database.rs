@@ -1,55 +1,217 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
-use std::path::Path;
+use std::{path::Path, time::Duration};
 
-use sqlx::SqlitePool;
+use sqlx::{
+    FromRow, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+};
 
-use crate::{DbUuid, Information};
+use crate::{DatabaseError, DatabaseResult, DbUuid, Information, SchemaVersion};
+
+/// Journal mode of the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JournalMode {
+    /// Write-ahead log.
+    #[default]
+    Wal,
+    /// Rollback journal that is deleted at the end of each transaction.
+    Delete,
+    /// Rollback journal in volatile memory.
+    Memory,
+}
+
+impl JournalMode {
+    const fn to_sqlite(self) -> SqliteJournalMode {
+        match self {
+            Self::Wal => SqliteJournalMode::Wal,
+            Self::Delete => SqliteJournalMode::Delete,
+            Self::Memory => SqliteJournalMode::Memory,
+        }
+    }
+}
+
+/// Accepted schema versions when opening a database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaVersionPolicy {
+    /// Only the latest major and minor version, see [`SchemaVersion::is_supported()`].
+    #[default]
+    Strict,
+    /// The latest major version with the same or a newer minor version.
+    MinorCompatible,
+    /// Any minor version of the latest major version.
+    MajorCompatible,
+}
+
+impl SchemaVersionPolicy {
+    /// Checks if the schema version is accepted.
+    #[must_use]
+    pub const fn accepts(self, schema_version: &SchemaVersion) -> bool {
+        match self {
+            Self::Strict => schema_version.is_supported(),
+            Self::MinorCompatible => SchemaVersion::LATEST.is_compatible_with(schema_version),
+            Self::MajorCompatible => schema_version.major == SchemaVersion::LATEST.major,
+        }
+    }
+}
+
+/// Options for [`open_database()`].
+#[derive(Debug, Clone)]
+pub struct OpenDatabaseOptions {
+    max_connections: u32,
+    acquire_timeout: Duration,
+    read_only: bool,
+    journal_mode: JournalMode,
+    foreign_keys: bool,
+    schema_version_policy: SchemaVersionPolicy,
+}
+
+impl OpenDatabaseOptions {
+    pub const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+
+    pub const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Maximum number of pooled connections.
+    #[must_use]
+    pub const fn max_connections(mut self, max_connections: u32) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Maximum time to wait for acquiring a pooled connection.
+    #[must_use]
+    pub const fn acquire_timeout(mut self, acquire_timeout: Duration) -> Self {
+        self.acquire_timeout = acquire_timeout;
+        self
+    }
+
+    /// Open the database in read-only mode.
+    ///
+    /// The journal mode is left unchanged for read-only databases.
+    #[must_use]
+    pub const fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Journal mode of the database.
+    #[must_use]
+    pub const fn journal_mode(mut self, journal_mode: JournalMode) -> Self {
+        self.journal_mode = journal_mode;
+        self
+    }
+
+    /// Enforce foreign key constraints.
+    #[must_use]
+    pub const fn foreign_keys(mut self, foreign_keys: bool) -> Self {
+        self.foreign_keys = foreign_keys;
+        self
+    }
+
+    /// Accepted schema versions.
+    #[must_use]
+    pub const fn schema_version_policy(
+        mut self,
+        schema_version_policy: SchemaVersionPolicy,
+    ) -> Self {
+        self.schema_version_policy = schema_version_policy;
+        self
+    }
+
+    async fn connect(&self, file_path: &Path) -> sqlx::Result<SqlitePool> {
+        let Self {
+            max_connections,
+            acquire_timeout,
+            read_only,
+            journal_mode,
+            foreign_keys,
+            schema_version_policy: _,
+        } = self;
+        let mut connect_options = SqliteConnectOptions::new()
+            .filename(file_path)
+            .read_only(*read_only)
+            .foreign_keys(*foreign_keys);
+        if !read_only {
+            connect_options = connect_options.journal_mode(journal_mode.to_sqlite());
+        }
+        SqlitePoolOptions::new()
+            .max_connections(*max_connections)
+            .acquire_timeout(*acquire_timeout)
+            .connect_with(connect_options)
+            .await
+    }
+}
+
+impl Default for OpenDatabaseOptions {
+    fn default() -> Self {
+        Self {
+            max_connections: Self::DEFAULT_MAX_CONNECTIONS,
+            acquire_timeout: Self::DEFAULT_ACQUIRE_TIMEOUT,
+            read_only: false,
+            journal_mode: JournalMode::default(),
+            foreign_keys: true,
+            schema_version_policy: SchemaVersionPolicy::default(),
+        }
+    }
+}
 
 pub async fn open_database(
     file_path: impl AsRef<Path>,
     db_uuid: Option<&DbUuid>,
-) -> sqlx::Result<(SqlitePool, Information)> {
-    let database_url = format!(
-        "sqlite:{file_path}",
-        file_path = file_path.as_ref().display()
-    );
-    let pool = SqlitePool::connect(&database_url).await?;
+    options: &OpenDatabaseOptions,
+) -> DatabaseResult<(SqlitePool, Information)> {
+    let pool = options.connect(file_path.as_ref()).await?;
+    let info = load_information(&pool, db_uuid, options.schema_version_policy).await?;
+    Ok((pool, info))
+}
+
+/// Opens an existing database in read-only mode.
+///
+/// All attempts to modify the database through the returned pool
+/// will fail. Useful for analysis workflows that must never write.
+pub async fn open_database_read_only(
+    file_path: impl AsRef<Path>,
+) -> DatabaseResult<(SqlitePool, Information)> {
+    let options = OpenDatabaseOptions::default().read_only(true);
+    open_database(file_path, None, &options).await
+}
+
+async fn load_information(
+    pool: &SqlitePool,
+    db_uuid: Option<&DbUuid>,
+    schema_version_policy: SchemaVersionPolicy,
+) -> DatabaseResult<Information> {
     let info = if let Some(db_uuid) = &db_uuid {
-        if let Some(info) = Information::try_load_by_uuid(&pool, db_uuid).await? {
+        if let Some(info) = Information::try_load_by_uuid(pool, db_uuid).await? {
             info
         } else {
-            // TODO: Use a custom error type.
             log::warn!("Found no database information record with UUID {db_uuid}");
-            return Err(sqlx::Error::RowNotFound);
+            return Err(DatabaseError::RowNotFound);
         }
     } else {
-        let mut info_all = Information::load_all(&pool).await?;
+        let mut info_all = Information::load_all(pool).await?;
         let info_count = info_all.len();
         let Some(info) = info_all.pop() else {
-            // TODO: Use a custom error type.
             log::warn!("Found no database information records");
-            return Err(sqlx::Error::RowNotFound);
+            return Err(DatabaseError::RowNotFound);
         };
         // Only a single row is expected.
         if !info_all.is_empty() {
-            // TODO: Use a custom error type.
             log::warn!("Found multiple ({info_count}) database information records");
-            return Err(sqlx::Error::RowNotFound);
+            return Err(DatabaseError::Ambiguous);
         }
         info
     };
-    if !info.schema_version().is_supported() {
-        // TODO: Use a custom error type.
+    let schema_version = info.schema_version();
+    if !schema_version_policy.accepts(&schema_version) {
         log::error!(
             "Found database {uuid} with unsupported schema version {schema_version}",
             uuid = info.uuid(),
-            schema_version = info.schema_version()
         );
-        return Err(sqlx::Error::RowNotFound);
+        return Err(DatabaseError::SchemaVersionUnsupported(schema_version));
     }
-    Ok((pool, info))
+    Ok(info)
 }
 
 pub async fn optimize_database(pool: &SqlitePool) -> sqlx::Result<()> {
@@ -57,3 +219,69 @@ pub async fn optimize_database(pool: &SqlitePool) -> sqlx::Result<()> {
     sqlx::query(r"ANALYZE").execute(pool).await?;
     Ok(())
 }
+
+/// Writes a defragmented copy of the database into a new file.
+///
+/// Uses `VACUUM INTO` which leaves the source database untouched.
+/// Fails if the destination file already exists. Fails with
+/// [`DatabaseError::UnsupportedPath`] if the destination path is not
+/// valid UTF-8.
+pub async fn backup_database(
+    pool: &SqlitePool,
+    destination: impl AsRef<Path>,
+) -> DatabaseResult<()> {
+    let destination = destination.as_ref();
+    let Some(destination) = destination.to_str() else {
+        return Err(DatabaseError::UnsupportedPath(destination.to_path_buf()));
+    };
+    sqlx::query(r"VACUUM INTO ?1")
+        .bind(destination)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Runs the integrity check of the database.
+///
+/// Returns all reported problems. An empty result indicates that
+/// no problems have been found.
+pub async fn check_integrity(pool: &SqlitePool) -> anyhow::Result<Vec<String>> {
+    let messages = sqlx::query_scalar::<_, String>(r"PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await?;
+    Ok(messages
+        .into_iter()
+        .filter(|message| message != "ok")
+        .collect())
+}
+
+/// A foreign key constraint violation.
+///
+/// Reported by [`check_foreign_keys()`].
+#[derive(Debug, Clone, FromRow)]
+pub struct ForeignKeyViolation {
+    /// The table that contains the referencing row.
+    pub table: String,
+
+    /// The referencing row.
+    ///
+    /// `None` for tables without a rowid.
+    pub rowid: Option<i64>,
+
+    /// The referenced table.
+    pub parent: String,
+
+    /// Index of the failed foreign key constraint in the referencing table.
+    pub fkid: i64,
+}
+
+/// Checks all foreign key constraints.
+///
+/// Returns all violations. An empty result indicates that
+/// no violations have been found.
+pub async fn check_foreign_keys(pool: &SqlitePool) -> anyhow::Result<Vec<ForeignKeyViolation>> {
+    let violations = sqlx::query_as(r"PRAGMA foreign_key_check")
+        .fetch_all(pool)
+        .await?;
+    Ok(violations)
+}
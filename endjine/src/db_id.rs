@@ -39,6 +39,13 @@ macro_rules! db_id {
                 Self(raw_id)
             }
 
+            /// Returns the raw value.
+            #[must_use]
+            #[allow(dead_code, reason = "not used for each generated type")]
+            pub const fn to_raw(self) -> i64 {
+                self.0
+            }
+
             /// Checks if the ID is valid.
             #[must_use]
             pub const fn is_valid(self) -> bool {
@@ -20,6 +20,17 @@ macro_rules! db_uuid {
                 ))
             }
 
+            /// Generates a new, random UUID (version 4).
+            #[must_use]
+            pub fn new_random() -> Self {
+                Self::from_uuid(sqlx::types::Uuid::new_v4())
+            }
+
+            #[must_use]
+            pub const fn from_uuid(uuid: sqlx::types::Uuid) -> Self {
+                Self(sqlx::types::uuid::fmt::Hyphenated::from_uuid(uuid))
+            }
+
             #[must_use]
             pub const fn is_nil(&self) -> bool {
                 self.0.as_uuid().is_nil()
@@ -37,6 +48,37 @@ macro_rules! db_uuid {
             }
         }
 
+        impl std::str::FromStr for $name {
+            type Err = sqlx::types::uuid::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                sqlx::types::Uuid::parse_str(s).map(Self::from_uuid)
+            }
+        }
+
+        /// Serialized as hyphenated string.
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.collect_str(&self.0)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let value =
+                    <std::borrow::Cow<'de, str> as serde::Deserialize>::deserialize(deserializer)?;
+                value.parse().map_err(serde::de::Error::custom)
+            }
+        }
+
         impl sqlx::Type<sqlx::Sqlite> for $name {
             fn type_info() -> sqlx::sqlite::SqliteTypeInfo {
                 <sqlx::types::uuid::fmt::Hyphenated as sqlx::Type<sqlx::Sqlite>>::type_info()
@@ -93,4 +135,29 @@ mod tests {
         assert!(DbUuid::default().is_nil());
         assert_eq!(DbUuid::default(), DbUuid::nil());
     }
+
+    #[test]
+    fn new_random_is_not_nil() {
+        assert!(!DbUuid::new_random().is_nil());
+        assert_ne!(DbUuid::new_random(), DbUuid::new_random());
+    }
+
+    #[test]
+    fn display_from_str_roundtrip() {
+        let uuid = "d67e6a4a-1979-44d7-a686-c41d64fc891c";
+        let db_uuid = uuid.parse::<DbUuid>().unwrap();
+        assert_eq!(uuid, db_uuid.to_string());
+        assert!("not-a-uuid".parse::<DbUuid>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_roundtrip() {
+        let db_uuid = "d67e6a4a-1979-44d7-a686-c41d64fc891c"
+            .parse::<DbUuid>()
+            .unwrap();
+        let json = serde_json::to_string(&db_uuid).unwrap();
+        assert_eq!("\"d67e6a4a-1979-44d7-a686-c41d64fc891c\"", json);
+        assert_eq!(db_uuid, serde_json::from_str(&json).unwrap());
+    }
 }
@@ -0,0 +1,61 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::{error::Error, fmt, path::PathBuf};
+
+use crate::SchemaVersion;
+
+/// Errors when accessing the database.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DatabaseError {
+    /// Failed to execute a query.
+    SqlxError(sqlx::Error),
+    /// The schema version of the database is not supported.
+    SchemaVersionUnsupported(SchemaVersion),
+    /// The requested row does not exist.
+    RowNotFound,
+    /// Found more than one row where a single row was expected.
+    Ambiguous,
+    /// The contents of the database are inconsistent.
+    InvalidState(String),
+    /// The file path is not supported by the database, e.g. if it is not valid UTF-8.
+    UnsupportedPath(PathBuf),
+}
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SqlxError(err) => err.fmt(f),
+            Self::SchemaVersionUnsupported(schema_version) => {
+                write!(f, "unsupported schema version {schema_version}")
+            }
+            Self::RowNotFound => f.write_str("row not found"),
+            Self::Ambiguous => f.write_str("ambiguous rows"),
+            Self::InvalidState(msg) => write!(f, "invalid state: {msg}"),
+            Self::UnsupportedPath(path) => {
+                write!(f, "unsupported path \"{path}\"", path = path.display())
+            }
+        }
+    }
+}
+
+impl Error for DatabaseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::SqlxError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for DatabaseError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => Self::RowNotFound,
+            err => Self::SqlxError(err),
+        }
+    }
+}
+
+pub type DatabaseResult<T> = Result<T, DatabaseError>;
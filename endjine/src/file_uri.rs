@@ -0,0 +1,133 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! Conversion between local file paths and `file://` URIs.
+
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, bail};
+
+const FILE_URI_PREFIX: &str = "file://";
+
+/// Checks if the string looks like a URI with a scheme.
+#[must_use]
+pub(crate) fn is_uri(s: &str) -> bool {
+    s.split_once("://").is_some_and(|(scheme, _)| {
+        !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// Decodes a `file://` URI into a local file path.
+///
+/// Only URIs with an empty or `localhost` authority are supported.
+pub(crate) fn file_path_from_uri(uri: &str) -> anyhow::Result<PathBuf> {
+    let Some(encoded_path) = uri.strip_prefix(FILE_URI_PREFIX) else {
+        bail!("URI \"{uri}\" is not a (local) file path");
+    };
+    let encoded_path = encoded_path
+        .strip_prefix("localhost")
+        .unwrap_or(encoded_path);
+    if !encoded_path.starts_with('/') {
+        bail!("URI \"{uri}\" has an unsupported authority");
+    }
+    let path = percent_decode(encoded_path).with_context(|| format!("decode URI \"{uri}\""))?;
+    // Windows drive letters are preceded by a slash, e.g. "/C:/Music".
+    #[cfg(target_os = "windows")]
+    let path = match path.as_bytes() {
+        [b'/', _, b':', ..] => path[1..].to_owned(),
+        _ => path,
+    };
+    Ok(PathBuf::from(path))
+}
+
+/// Encodes an absolute local file path as a `file://` URI.
+///
+/// Fails if the path is relative or not valid UTF-8.
+pub(crate) fn file_path_to_uri(path: &Path) -> anyhow::Result<String> {
+    if !path.is_absolute() {
+        bail!("relative path \"{path}\"", path = path.display());
+    }
+    let Some(path) = path.to_str() else {
+        bail!("non-UTF-8 path \"{path}\"", path = path.display());
+    };
+    let path = path.replace('\\', "/");
+    let mut uri = String::with_capacity(FILE_URI_PREFIX.len() + 1 + path.len());
+    uri.push_str(FILE_URI_PREFIX);
+    if !path.starts_with('/') {
+        // Windows drive letter.
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'/' | b'-' | b'_' | b'.' | b'~' | b':') {
+            uri.push(char::from(byte));
+        } else {
+            write!(uri, "%{byte:02X}").expect("infallible");
+        }
+    }
+    Ok(uri)
+}
+
+pub(crate) fn percent_decode(encoded: &str) -> anyhow::Result<String> {
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut bytes = encoded.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            decoded.push(byte);
+            continue;
+        }
+        let (Some(high), Some(low)) = (bytes.next(), bytes.next()) else {
+            bail!("incomplete percent-encoding");
+        };
+        let (Some(high), Some(low)) = (char::from(high).to_digit(16), char::from(low).to_digit(16))
+        else {
+            bail!("invalid percent-encoding");
+        };
+        #[expect(clippy::cast_possible_truncation, reason = "two hex digits")]
+        decoded.push((high * 16 + low) as u8);
+    }
+    String::from_utf8(decoded).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{file_path_from_uri, file_path_to_uri, is_uri};
+
+    #[test]
+    fn detect_uri() {
+        assert!(is_uri("file:///music/a.mp3"));
+        assert!(is_uri("http://example.com/a.mp3"));
+        assert!(!is_uri("/music/a.mp3"));
+        assert!(!is_uri("music/a://b.mp3"));
+        assert!(!is_uri("://a.mp3"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn file_uri_roundtrip() {
+        let path = Path::new("/music/Artist Name/100% Pure.mp3");
+        let uri = file_path_to_uri(path).unwrap();
+        assert_eq!(uri, "file:///music/Artist%20Name/100%25%20Pure.mp3");
+        assert_eq!(file_path_from_uri(&uri).unwrap(), path);
+        assert_eq!(
+            file_path_from_uri("file://localhost/music/a.mp3").unwrap(),
+            Path::new("/music/a.mp3")
+        );
+    }
+
+    #[test]
+    fn invalid_file_uri() {
+        assert!(file_path_from_uri("http://example.com/a.mp3").is_err());
+        assert!(file_path_from_uri("file://example.com/a.mp3").is_err());
+        assert!(file_path_from_uri("file:///a%2").is_err());
+        assert!(file_path_from_uri("file:///a%zz").is_err());
+        assert!(file_path_to_uri(Path::new("music/a.mp3")).is_err());
+    }
+}
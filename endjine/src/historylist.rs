@@ -1,10 +1,12 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
-use futures_util::stream::BoxStream;
-use sqlx::{FromRow, SqliteExecutor};
+use std::io::Write;
 
-use crate::{DbUuid, TrackId, UnixTimestamp};
+use futures_util::{TryStreamExt as _, stream::BoxStream};
+use sqlx::{Connection as _, FromRow, SqliteConnection, SqliteExecutor};
+
+use crate::{DbUuid, LibraryPath, TrackId, UnixTimestamp};
 
 crate::db_id!(HistorylistId);
 
@@ -44,6 +46,98 @@ impl Historylist {
         sqlx::query_as(r#"SELECT * FROM "Historylist" ORDER BY "id""#).fetch(executor)
     }
 
+    /// Fetches the most recent [`Historylist`]s asynchronously.
+    ///
+    /// Fetches at most `limit` sessions, ordered by start time in
+    /// descending order, i.e. the latest session comes first.
+    #[must_use]
+    pub fn fetch_recent<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        limit: u64,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(
+            r#"SELECT * FROM "Historylist" ORDER BY "startTime" DESC, "id" DESC LIMIT ?1"#,
+        )
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch(executor)
+    }
+
+    /// Loads the [`Historylist`] with the latest start time.
+    ///
+    /// Returns `Ok(None)` if no sessions have been recorded.
+    pub async fn try_load_latest(executor: impl SqliteExecutor<'_>) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as(
+            r#"SELECT * FROM "Historylist" ORDER BY "startTime" DESC, "id" DESC LIMIT 1"#,
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    /// Counts all [`Historylist`]s.
+    pub async fn count_all(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
+        let count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "Historylist""#)
+            .fetch_one(executor)
+            .await?;
+        debug_assert!(count >= 0);
+        Ok(count.cast_unsigned())
+    }
+
+    /// Exports the played tracks of a session as M3U.
+    ///
+    /// Writes the absolute file paths of all tracks ordered by start time,
+    /// one per line. Entries without a track file path are skipped.
+    ///
+    /// Returns the number of entries written.
+    pub async fn export_to_m3u<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        library_path: &LibraryPath,
+        session_id: i64,
+        mut output: impl Write,
+    ) -> anyhow::Result<u64> {
+        let mut paths = sqlx::query_scalar::<_, String>(
+            r#"SELECT "Track"."path" FROM "HistorylistEntity"
+               JOIN "Historylist" ON "Historylist"."id"="HistorylistEntity"."listId"
+               JOIN "Track" ON "Track"."id"="HistorylistEntity"."trackId"
+               WHERE "Historylist"."sessionId"=?1 AND "Track"."path" IS NOT NULL
+               ORDER BY "HistorylistEntity"."startTime","HistorylistEntity"."id""#,
+        )
+        .bind(session_id)
+        .fetch(executor);
+        let mut entry_count = 0;
+        while let Some(path) = paths.try_next().await? {
+            let mut file_path = library_path.file_path().clone();
+            file_path.append_relative_suffix(&path);
+            writeln!(output, "{file_path}")?;
+            entry_count += 1;
+        }
+        output.flush()?;
+        Ok(entry_count)
+    }
+
+    /// Deletes a [`Historylist`] including all its entries.
+    ///
+    /// The schema does not declare `ON DELETE CASCADE` for the entries in
+    /// `HistorylistEntity`. Deleting only the [`Historylist`] would leave
+    /// orphaned entries behind. Both are deleted within a single transaction,
+    /// which becomes a savepoint if a transaction is already in progress.
+    ///
+    /// Returns `true` if the [`Historylist`] has been deleted and `false`
+    /// if it has not been found.
+    pub async fn delete(
+        connection: &mut SqliteConnection,
+        id: HistorylistId,
+    ) -> sqlx::Result<bool> {
+        let mut tx = connection.begin().await?;
+        HistorylistEntity::delete_list(&mut *tx, id).await?;
+        let result = sqlx::query(r#"DELETE FROM "Historylist" WHERE "id"=?1"#)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        debug_assert!(result.rows_affected() <= 1);
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Loads a single [`Historylist`] by ID.
     ///
     /// Returns `Ok(None)` if the requested [`Historylist`] has not been found.
@@ -109,4 +203,32 @@ impl HistorylistEntity {
             .fetch_optional(executor)
             .await
     }
+
+    /// Counts the items of a list.
+    pub async fn count_list(
+        executor: impl SqliteExecutor<'_>,
+        list_id: HistorylistId,
+    ) -> sqlx::Result<u64> {
+        let count: i64 =
+            sqlx::query_scalar(r#"SELECT COUNT(*) FROM "HistorylistEntity" WHERE "listId"=?1"#)
+                .bind(list_id)
+                .fetch_one(executor)
+                .await?;
+        debug_assert!(count >= 0);
+        Ok(count.cast_unsigned())
+    }
+
+    /// Deletes all items of a list.
+    ///
+    /// Returns the number of deleted items.
+    pub async fn delete_list(
+        executor: impl SqliteExecutor<'_>,
+        list_id: HistorylistId,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query(r#"DELETE FROM "HistorylistEntity" WHERE "listId"=?1"#)
+            .bind(list_id)
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
 }
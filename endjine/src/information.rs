@@ -3,11 +3,10 @@
 
 use std::fmt;
 
-use anyhow::bail;
 use futures_util::StreamExt as _;
-use sqlx::{FromRow, SqliteExecutor};
+use sqlx::{FromRow, SqliteConnection, SqliteExecutor};
 
-use crate::DbUuid;
+use crate::{DatabaseError, DatabaseResult, DbUuid};
 
 /// Latest schema major version.
 ///
@@ -27,6 +26,13 @@ pub struct SchemaVersion {
 }
 
 impl SchemaVersion {
+    /// Latest supported schema version.
+    pub const LATEST: Self = Self {
+        major: SCHEMA_VERSION_MAJOR,
+        minor: SCHEMA_VERSION_MINOR,
+        patch: 0,
+    };
+
     #[must_use]
     pub const fn is_supported(&self) -> bool {
         let Self {
@@ -36,6 +42,16 @@ impl SchemaVersion {
         } = self;
         *major == SCHEMA_VERSION_MAJOR && *minor == SCHEMA_VERSION_MINOR
     }
+
+    /// Checks if `other` is a compatible, newer or equal version.
+    ///
+    /// Both versions must have the same major version. The minor version
+    /// of `other` must not be less than the minor version of `self`. The
+    /// patch versions are ignored.
+    #[must_use]
+    pub const fn is_compatible_with(&self, other: &Self) -> bool {
+        self.major == other.major && self.minor <= other.minor
+    }
 }
 
 impl fmt::Display for SchemaVersion {
@@ -118,24 +134,58 @@ impl Information {
     /// Loads the singular entry.
     ///
     /// Fails if the table contains none or more than one entry.
-    pub async fn load<'e, E>(mut executor: impl FnMut() -> E) -> anyhow::Result<Self>
-    where
-        E: SqliteExecutor<'e>,
-    {
+    pub async fn load(connection: &mut SqliteConnection) -> DatabaseResult<Self> {
         let mut row_results =
-            sqlx::query_as(r#"SELECT * FROM "Information" LIMIT 2"#).fetch(executor());
+            sqlx::query_as(r#"SELECT * FROM "Information" LIMIT 2"#).fetch(&mut *connection);
         let Some(row_result) = row_results.next().await else {
             // Table is empty.
-            debug_assert_eq!(Self::count_all(executor()).await.ok(), Some(0));
-            return Err(sqlx::Error::RowNotFound.into());
+            drop(row_results);
+            debug_assert_eq!(Self::count_all(&mut *connection).await.ok(), Some(0));
+            return Err(DatabaseError::RowNotFound);
         };
         let row = row_result?;
         if row_results.next().await.is_some() {
-            bail!("ambiguous");
+            return Err(DatabaseError::Ambiguous);
         }
         Ok(row)
     }
 
+    /// Creates the singular entry.
+    ///
+    /// Fails with [`DatabaseError::InvalidState`] if the table already
+    /// contains an entry.
+    pub async fn create(
+        executor: impl SqliteExecutor<'_>,
+        uuid: DbUuid,
+        schema_version: SchemaVersion,
+    ) -> DatabaseResult<InformationId> {
+        let SchemaVersion {
+            major,
+            minor,
+            patch,
+        } = schema_version;
+        // Check and insert atomically within a single statement.
+        let result = sqlx::query(
+            r#"INSERT INTO "Information"
+               ("uuid", "schemaVersionMajor", "schemaVersionMinor", "schemaVersionPatch")
+               SELECT ?1, ?2, ?3, ?4
+               WHERE NOT EXISTS (SELECT 1 FROM "Information")"#,
+        )
+        .bind(uuid)
+        .bind(major)
+        .bind(minor)
+        .bind(patch)
+        .execute(executor)
+        .await?;
+        debug_assert!(result.rows_affected() <= 1);
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::InvalidState(
+                "database information already exists".to_owned(),
+            ));
+        }
+        Ok(InformationId::new(result.last_insert_rowid()))
+    }
+
     /// Eagerly loads all [`Information`] at once.
     ///
     /// Unfiltered and in no particular order.
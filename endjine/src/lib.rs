@@ -8,67 +8,106 @@ mod album_art;
 
 use std::{
     borrow::Cow,
-    fmt,
+    ffi::OsStr,
+    fmt, fs,
     ops::Deref,
     path::{Component, Path, PathBuf},
+    str::FromStr,
 };
 
 use anyhow::bail;
 use relative_path::{RelativePath, RelativePathBuf};
+use sqlx::types::time::{OffsetDateTime, PrimitiveDateTime};
 
 pub use self::album_art::{AlbumArt, AlbumArtId, AlbumArtImageQuality};
 
+mod bpm;
+pub use self::bpm::Bpm;
+
 mod changelog;
 pub use self::changelog::{ChangeLog, ChangeLogId};
 
 mod database;
-pub use self::database::{open_database, optimize_database};
+pub use self::database::{
+    ForeignKeyViolation, JournalMode, OpenDatabaseOptions, SchemaVersionPolicy, backup_database,
+    check_foreign_keys, check_integrity, open_database, open_database_read_only, optimize_database,
+};
 
 mod db_id;
 
+mod error;
+pub use self::error::{DatabaseError, DatabaseResult};
+
 mod db_uuid;
 pub use self::db_uuid::DbUuid;
 
+mod file_uri;
+
 mod historylist;
 pub use self::historylist::{Historylist, HistorylistEntity, HistorylistEntityId, HistorylistId};
 
 mod information;
 pub use self::information::{
-    Information, InformationId, SCHEMA_VERSION_MAJOR, SCHEMA_VERSION_MINOR,
+    Information, InformationId, SCHEMA_VERSION_MAJOR, SCHEMA_VERSION_MINOR, SchemaVersion,
 };
 
+mod m3u;
+pub use self::m3u::{M3uWriter, import_m3u_file, read_m3u};
+
+mod musical_key;
+pub use self::musical_key::MusicalKey;
+
 mod pack;
 pub use self::pack::{Pack, PackId, PackUuid};
 
 mod performance;
-pub use self::performance::{PerformanceData, PerformanceDataId};
+pub use self::performance::{
+    BeatGridEntry, CuePoint, LoopRegion, PerformanceData, PerformanceDataId, WaveformSample,
+};
+
+mod pls;
+pub use self::pls::{export_playlist_to_pls, import_pls_file, read_pls};
 
 mod playlist;
 pub use self::playlist::{
-    PLAYLIST_PATH_SEGMENT_SEPARATOR, Playlist, PlaylistAllChildren, PlaylistAllChildrenId,
-    PlaylistAllParent, PlaylistAllParentId, PlaylistEntity, PlaylistEntityId, PlaylistId,
-    PlaylistPath, PlaylistPathId, concat_playlist_path_segments_to_string,
-    is_valid_playlist_path_segment, resolve_playlist_track_refs_from_file_paths,
+    ImportPlaylistMode, PLAYLIST_PATH_SEGMENT_SEPARATOR, Playlist, PlaylistAllChildren,
+    PlaylistAllChildrenId, PlaylistAllParent, PlaylistAllParentId, PlaylistEntity,
+    PlaylistEntityId, PlaylistEntityWithTrack, PlaylistId, PlaylistNode, PlaylistPath,
+    PlaylistPathId, concat_playlist_path_segments_to_string, is_valid_playlist_path_segment,
+    resolve_playlist_track_refs_from_file_paths, split_playlist_path_into_segments,
 };
 
 mod preparelist;
 pub use self::preparelist::{PreparelistEntity, PreparelistEntityId};
 
+mod rating;
+pub use self::rating::Rating;
+
 mod smartlist;
 pub use self::smartlist::{
-    Smartlist, SmartlistRules, SmartlistRulesItem, SmartlistRulesMatch, SmartlistUuid,
+    Smartlist, SmartlistColumn, SmartlistCondition, SmartlistRules, SmartlistRulesItem,
+    SmartlistRulesMatch, SmartlistUuid,
 };
 
 mod track;
-pub use self::track::{OriginTrackRef, Track, TrackId, TrackRef, import_track_file_path};
+pub use self::track::{
+    OriginTrackRef, Track, TrackFilter, TrackId, TrackMetadata, TrackRef, TrackWithAlbumArt,
+    import_track_file_path,
+};
+
+#[cfg(feature = "testing")]
+pub mod testing;
 
 mod unix_timestamp;
 pub use self::unix_timestamp::UnixTimestamp;
 
+mod xspf;
+pub use self::xspf::{export_playlist_to_xspf, import_xspf_file, read_xspf};
+
 #[cfg(feature = "batch")]
 pub mod batch;
 #[cfg(feature = "batch")]
-pub use self::batch::BatchOutcome;
+pub use self::batch::{BatchOutcome, BatchProgress};
 
 /// Portable file path.
 ///
@@ -105,6 +144,24 @@ impl<'a> FilePath<'a> {
         relative
     }
 
+    /// The final component of the relative path part.
+    ///
+    /// Returns `None` if the relative path is empty or ends with `..`.
+    #[must_use]
+    pub fn file_name(&self) -> Option<&str> {
+        let Self { base: _, relative } = self;
+        relative.file_name()
+    }
+
+    /// The extension of [`Self::file_name()`].
+    ///
+    /// Returns `None` if the file name has no extension.
+    #[must_use]
+    pub fn extension(&self) -> Option<&str> {
+        let Self { base: _, relative } = self;
+        relative.extension()
+    }
+
     #[must_use]
     pub(crate) fn to_parent_path(&'a self) -> Option<Self> {
         let Self { base, relative } = self;
@@ -133,6 +190,16 @@ impl FilePath<'_> {
         Self::import_path_impl(path.as_ref())
     }
 
+    /// Parses a file system path.
+    ///
+    /// Fails if the string is empty.
+    pub fn try_from_str(s: &str) -> anyhow::Result<FilePath<'static>> {
+        if s.is_empty() {
+            bail!("empty file path");
+        }
+        Ok(Self::import_path_impl(Path::new(s)))
+    }
+
     #[must_use]
     fn import_path_impl(path: &Path) -> FilePath<'static> {
         if path.is_relative()
@@ -185,6 +252,18 @@ impl FilePath<'_> {
         }
     }
 
+    /// Replaces the extension of the file name.
+    ///
+    /// An empty extension removes the current extension.
+    #[must_use]
+    pub fn with_extension(&self, extension: &str) -> FilePath<'static> {
+        let Self { base, relative } = self;
+        FilePath {
+            base: Cow::Owned(base.to_path_buf()),
+            relative: Cow::Owned(relative.with_extension(extension)),
+        }
+    }
+
     /// Appends a relative path in-place.
     pub(crate) fn append_relative_suffix<P>(&mut self, suffix: &P)
     where
@@ -228,7 +307,21 @@ impl FilePath<'_> {
     #[must_use]
     pub fn to_path(&self) -> PathBuf {
         let Self { base, relative } = self;
-        relative.to_path(base)
+        // RelativePath::to_path() would duplicate the trailing separator
+        // of a root directory base path, e.g. "//foo" instead of "/foo".
+        let mut path = base.to_path_buf();
+        for component in relative.components() {
+            path.push(component.as_str());
+        }
+        path
+    }
+}
+
+impl FromStr for FilePath<'static> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from_str(s)
     }
 }
 
@@ -240,6 +333,15 @@ impl fmt::Display for FilePath<'_> {
 
 pub(crate) const LIBRARY_DIRECTORY_NAME: &str = "Engine Library";
 
+/// Directory within the _Engine Library_ that contains the database files.
+pub(crate) const DATABASE_DIRECTORY_NAME: &str = "Database2";
+
+/// File name of the main database.
+pub(crate) const DATABASE_FILE_NAME: &str = "m.db";
+
+/// Maximum directory depth below the search root for [`LibraryPath::discover()`].
+const DISCOVER_MAX_DEPTH: usize = 3;
+
 /// Directory that contains the _Engine Library_.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LibraryPath(FilePath<'static>);
@@ -264,11 +366,62 @@ impl LibraryPath {
         Ok(Self(library_path))
     }
 
+    /// Discovers library directories below a search root.
+    ///
+    /// Walks the directory tree up to a bounded depth and yields all
+    /// library directories that contain a database file. Directories
+    /// that could not be read are silently skipped.
+    pub fn discover(search_root: &Path) -> impl Iterator<Item = FilePath<'static>> {
+        let mut pending_dirs = vec![(search_root.to_path_buf(), 0)];
+        std::iter::from_fn(move || {
+            while let Some((dir_path, depth)) = pending_dirs.pop() {
+                let is_library_dir = dir_path
+                    .file_name()
+                    .and_then(OsStr::to_str)
+                    .is_some_and(|dir_name| dir_name.eq_ignore_ascii_case(LIBRARY_DIRECTORY_NAME));
+                if is_library_dir
+                    && dir_path
+                        .join(DATABASE_DIRECTORY_NAME)
+                        .join(DATABASE_FILE_NAME)
+                        .is_file()
+                {
+                    return Some(FilePath::import_path(&dir_path));
+                }
+                if depth >= DISCOVER_MAX_DEPTH {
+                    continue;
+                }
+                let Ok(entries) = fs::read_dir(&dir_path) else {
+                    continue;
+                };
+                let mut sub_dirs = entries
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+                    .map(|entry| entry.path())
+                    .collect::<Vec<_>>();
+                // Visit sub-directories in lexicographical order.
+                sub_dirs.sort_unstable_by(|lhs, rhs| rhs.cmp(lhs));
+                pending_dirs.extend(sub_dirs.into_iter().map(|sub_dir| (sub_dir, depth + 1)));
+            }
+            None
+        })
+    }
+
     #[must_use]
     pub const fn file_path(&self) -> &FilePath<'static> {
         let Self(inner) = self;
         inner
     }
+
+    /// The path of the main database file within the library directory.
+    #[must_use]
+    pub fn database_file_path(&self) -> FilePath<'static> {
+        let Self(inner) = self;
+        let mut file_path = inner.clone();
+        file_path.append_relative_suffix(
+            &RelativePath::new(DATABASE_DIRECTORY_NAME).join(DATABASE_FILE_NAME),
+        );
+        file_path
+    }
 }
 
 impl From<LibraryPath> for FilePath<'static> {
@@ -306,13 +459,20 @@ fn grandparent_file_path<'a>(file_path: &'a FilePath<'a>) -> Option<FilePath<'st
     parent_path.to_parent_path().map(FilePath::into_owned)
 }
 
+/// Current UTC time encoded as plain date/time.
+#[must_use]
+pub(crate) fn utc_now_primitive_date_time() -> PrimitiveDateTime {
+    let now = OffsetDateTime::now_utc();
+    PrimitiveDateTime::new(now.date(), now.time())
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
     use relative_path::RelativePath;
 
-    use crate::FilePath;
+    use crate::{FilePath, LibraryPath};
 
     #[test]
     fn import_file_path() {
@@ -355,4 +515,87 @@ mod tests {
         assert_eq!(file_path.base(), root_path);
         assert_eq!(file_path.relative(), RelativePath::new("../foo"));
     }
+
+    #[test]
+    fn file_path_roundtrip() {
+        #[cfg(target_os = "windows")]
+        let root_path = Path::new("C:\\");
+        #[cfg(not(target_os = "windows"))]
+        let root_path = Path::new("/");
+
+        let path = root_path.join("foo").join("bar.mp3");
+        assert_eq!(FilePath::import_path(&path).to_path(), path);
+
+        let path = Path::new("..").join("foo").join("bar.mp3");
+        assert_eq!(FilePath::import_path(&path).to_path(), path);
+    }
+
+    #[test]
+    fn file_name_and_extension() {
+        let file_path = FilePath::import_path("../Music/track.flac");
+        assert_eq!(file_path.file_name(), Some("track.flac"));
+        assert_eq!(file_path.extension(), Some("flac"));
+
+        let file_path = FilePath::import_path("../Music/track");
+        assert_eq!(file_path.file_name(), Some("track"));
+        assert_eq!(file_path.extension(), None);
+
+        let file_path = FilePath::import_path("../Music/.hidden");
+        assert_eq!(file_path.file_name(), Some(".hidden"));
+        assert_eq!(file_path.extension(), None);
+
+        let file_path = FilePath::import_path("..");
+        assert_eq!(file_path.file_name(), None);
+        assert_eq!(file_path.extension(), None);
+    }
+
+    #[test]
+    fn library_database_file_path() {
+        let db_file_path = FilePath::import_path(
+            &Path::new("/")
+                .join("Engine Library")
+                .join("Database2")
+                .join("m.db"),
+        );
+        let library_path = LibraryPath::new(&db_file_path).unwrap();
+        assert_eq!(library_path.database_file_path(), db_file_path);
+    }
+
+    #[test]
+    fn try_from_str() {
+        assert!(FilePath::try_from_str("").is_err());
+        assert_eq!(
+            FilePath::try_from_str("../Music/./track.flac").unwrap(),
+            FilePath::import_path("../Music/track.flac")
+        );
+        let path = Path::new("/").join("Music").join("track.flac");
+        assert_eq!(
+            path.to_str().unwrap().parse::<FilePath<'static>>().unwrap(),
+            FilePath::import_path(&path)
+        );
+    }
+
+    #[test]
+    fn with_extension() {
+        let file_path = FilePath::import_path(&Path::new("/").join("Music").join("track.flac"));
+        let sidecar_path = file_path.with_extension("cue");
+        assert_eq!(sidecar_path.base(), file_path.base());
+        assert_eq!(
+            sidecar_path.relative(),
+            RelativePath::new("Music/track.cue")
+        );
+
+        let file_path = FilePath::import_path("../Music/track");
+        assert_eq!(
+            file_path.with_extension("lrc").relative(),
+            RelativePath::new("../Music/track.lrc")
+        );
+        assert_eq!(
+            file_path
+                .with_extension("lrc")
+                .with_extension("")
+                .relative(),
+            RelativePath::new("../Music/track")
+        );
+    }
 }
@@ -0,0 +1,163 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+};
+
+use anyhow::{Context as _, bail};
+
+use crate::{
+    FilePath,
+    file_uri::{file_path_from_uri, is_uri},
+};
+
+/// Imports the file paths from an M3U playlist file.
+///
+/// Relative file paths are resolved against `base_path` that defaults
+/// to the parent directory of the M3U file.
+pub fn import_m3u_file(
+    path: &Path,
+    base_path: Option<&Path>,
+) -> anyhow::Result<Vec<FilePath<'static>>> {
+    let file = File::open(path).context("open M3U file")?;
+    let base_path = base_path.or_else(|| path.parent());
+    read_m3u(BufReader::new(file), base_path)
+}
+
+/// Reads the file paths from a plain or extended M3U playlist.
+///
+/// Comments and directives like `#EXTINF` are ignored. Entries are
+/// either file paths or `file://` URIs.
+///
+/// Relative file paths are resolved against `base_path`. Fails if no
+/// `base_path` is given and the playlist contains relative file paths.
+pub fn read_m3u(
+    reader: impl BufRead,
+    base_path: Option<&Path>,
+) -> anyhow::Result<Vec<FilePath<'static>>> {
+    let mut file_paths = Vec::new();
+    for (line_index, line) in reader.lines().enumerate() {
+        let line = line?;
+        // The UTF-8 BOM is not stripped when reading lines.
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line_number = line_index + 1;
+        let file_path = import_m3u_entry(line, base_path)
+            .with_context(|| format!("invalid file path in line {line_number}"))?;
+        file_paths.push(file_path);
+    }
+    Ok(file_paths)
+}
+
+fn import_m3u_entry(entry: &str, base_path: Option<&Path>) -> anyhow::Result<FilePath<'static>> {
+    let file_path = if is_uri(entry) {
+        file_path_from_uri(entry)?
+    } else {
+        Path::new(entry).to_path_buf()
+    };
+    if file_path.is_absolute() {
+        return Ok(FilePath::import_path(&file_path));
+    }
+    let Some(base_path) = base_path else {
+        bail!("unresolved relative file path \"{entry}\"");
+    };
+    Ok(FilePath::import_path(&base_path.join(file_path)))
+}
+
+/// Writes playlists in the extended M3U format.
+///
+/// The `#EXTM3U` header is written when creating the writer.
+#[derive(Debug)]
+pub struct M3uWriter<W> {
+    output: W,
+}
+
+impl<W: Write> M3uWriter<W> {
+    /// Creates a new writer and writes the header.
+    pub fn new(mut output: W) -> io::Result<Self> {
+        writeln!(output, "#EXTM3U")?;
+        Ok(Self { output })
+    }
+
+    /// Writes a single entry.
+    ///
+    /// The `#EXTINF` line with the duration and the display name is only
+    /// written if the duration is known.
+    pub fn write_entry(
+        &mut self,
+        duration_secs: Option<u64>,
+        display_name: &str,
+        path: &Path,
+    ) -> io::Result<()> {
+        let Self { output } = self;
+        if let Some(duration_secs) = duration_secs {
+            // Line breaks would corrupt the file.
+            let display_name = display_name.replace(['\r', '\n'], " ");
+            writeln!(output, "#EXTINF:{duration_secs},{display_name}")?;
+        }
+        writeln!(output, "{path}", path = path.display())
+    }
+
+    /// Flushes and returns the output.
+    pub fn finish(self) -> io::Result<W> {
+        let Self { mut output } = self;
+        output.flush()?;
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::FilePath;
+
+    use super::{M3uWriter, read_m3u};
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn read_entries() {
+        let m3u = "\
+\u{feff}#EXTM3U
+#EXTINF:185,Artist - Title
+/music/a.mp3
+
+b.mp3
+file:///music/c%20d.mp3
+";
+        let file_paths = read_m3u(m3u.as_bytes(), Some(Path::new("/base"))).unwrap();
+        assert_eq!(
+            file_paths,
+            [
+                FilePath::import_path("/music/a.mp3"),
+                FilePath::import_path("/base/b.mp3"),
+                FilePath::import_path("/music/c d.mp3"),
+            ]
+        );
+        assert!(read_m3u("b.mp3\n".as_bytes(), None).is_err());
+    }
+
+    #[test]
+    fn write_entries() {
+        let mut writer = M3uWriter::new(Vec::new()).unwrap();
+        writer
+            .write_entry(Some(185), "Artist - Title", Path::new("/music/a.mp3"))
+            .unwrap();
+        writer
+            .write_entry(None, "Ignored", Path::new("/music/b.mp3"))
+            .unwrap();
+        writer
+            .write_entry(Some(0), "Line\nbreak", Path::new("/music/c.mp3"))
+            .unwrap();
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            "#EXTM3U\n#EXTINF:185,Artist - Title\n/music/a.mp3\n/music/b.mp3\n#EXTINF:0,Line break\n/music/c.mp3\n"
+        );
+    }
+}
@@ -0,0 +1,139 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::fmt;
+
+/// Musical key of a track in Camelot notation.
+///
+/// Encoded as integer in the `key` column of `Track`:
+///
+/// - 0: Unknown, i.e. no key
+/// - 1..=12: Major keys 1B..12B
+/// - 13..=24: Minor keys 1A..12A
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum MusicalKey {
+    Unknown = 0,
+    Camelot1B = 1,
+    Camelot2B = 2,
+    Camelot3B = 3,
+    Camelot4B = 4,
+    Camelot5B = 5,
+    Camelot6B = 6,
+    Camelot7B = 7,
+    Camelot8B = 8,
+    Camelot9B = 9,
+    Camelot10B = 10,
+    Camelot11B = 11,
+    Camelot12B = 12,
+    Camelot1A = 13,
+    Camelot2A = 14,
+    Camelot3A = 15,
+    Camelot4A = 16,
+    Camelot5A = 17,
+    Camelot6A = 18,
+    Camelot7A = 19,
+    Camelot8A = 20,
+    Camelot9A = 21,
+    Camelot10A = 22,
+    Camelot11A = 23,
+    Camelot12A = 24,
+}
+
+impl MusicalKey {
+    /// All keys ordered by their encoded value.
+    pub const ALL: [Self; 25] = [
+        Self::Unknown,
+        Self::Camelot1B,
+        Self::Camelot2B,
+        Self::Camelot3B,
+        Self::Camelot4B,
+        Self::Camelot5B,
+        Self::Camelot6B,
+        Self::Camelot7B,
+        Self::Camelot8B,
+        Self::Camelot9B,
+        Self::Camelot10B,
+        Self::Camelot11B,
+        Self::Camelot12B,
+        Self::Camelot1A,
+        Self::Camelot2A,
+        Self::Camelot3A,
+        Self::Camelot4A,
+        Self::Camelot5A,
+        Self::Camelot6A,
+        Self::Camelot7A,
+        Self::Camelot8A,
+        Self::Camelot9A,
+        Self::Camelot10A,
+        Self::Camelot11A,
+        Self::Camelot12A,
+    ];
+
+    /// Position on the Camelot wheel in the range 1..=12.
+    ///
+    /// Returns `None` for [`Self::Unknown`].
+    #[must_use]
+    pub const fn camelot_number(self) -> Option<u8> {
+        match self as u8 {
+            0 => None,
+            value @ 1..=12 => Some(value),
+            value => Some(value - 12),
+        }
+    }
+
+    /// Checks for a minor key, i.e. the inner Camelot wheel "A".
+    #[must_use]
+    pub const fn is_minor(self) -> bool {
+        self as u8 > 12
+    }
+}
+
+impl TryFrom<u8> for MusicalKey {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::ALL.get(usize::from(value)).copied().ok_or(value)
+    }
+}
+
+impl From<MusicalKey> for u8 {
+    fn from(from: MusicalKey) -> Self {
+        from as Self
+    }
+}
+
+impl fmt::Display for MusicalKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(number) = self.camelot_number() else {
+            return f.write_str("-");
+        };
+        let letter = if self.is_minor() { 'A' } else { 'B' };
+        write!(f, "{number}{letter}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MusicalKey;
+
+    #[test]
+    fn u8_roundtrip() {
+        for value in 0..=24 {
+            let key = MusicalKey::try_from(value).unwrap();
+            assert_eq!(value, u8::from(key));
+        }
+        assert_eq!(Err(25), MusicalKey::try_from(25));
+    }
+
+    #[test]
+    fn display_camelot() {
+        assert_eq!("-", MusicalKey::Unknown.to_string());
+        assert_eq!("1B", MusicalKey::Camelot1B.to_string());
+        assert_eq!("8B", MusicalKey::Camelot8B.to_string());
+        assert_eq!("12B", MusicalKey::Camelot12B.to_string());
+        assert_eq!("1A", MusicalKey::Camelot1A.to_string());
+        assert_eq!("3A", MusicalKey::Camelot3A.to_string());
+        assert_eq!("12A", MusicalKey::Camelot12A.to_string());
+    }
+}
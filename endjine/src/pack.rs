@@ -1,7 +1,8 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
-use sqlx::FromRow;
+use futures_util::stream::BoxStream;
+use sqlx::{FromRow, SqliteExecutor};
 
 use crate::{ChangeLogId, DbUuid, UnixTimestamp};
 
@@ -18,3 +19,51 @@ pub struct Pack {
     pub change_log_id: ChangeLogId,
     pub last_pack_time: UnixTimestamp,
 }
+
+impl Pack {
+    /// Fetches all [`Pack`]s asynchronously.
+    ///
+    /// Unfiltered and in no particular order.
+    #[must_use]
+    pub fn fetch_all<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "Pack""#).fetch(executor)
+    }
+
+    /// Loads a single [`Pack`] by ID.
+    ///
+    /// Returns `Ok(None)` if the requested [`Pack`] has not been found.
+    pub async fn try_load(
+        executor: impl SqliteExecutor<'_>,
+        id: PackId,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "Pack" WHERE "id"=?1"#)
+            .bind(id)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// Creates a new [`Pack`].
+    pub async fn create(
+        executor: impl SqliteExecutor<'_>,
+        pack_uuid: PackUuid,
+        change_log_db_uuid: DbUuid,
+        change_log_id: ChangeLogId,
+        pack_time: UnixTimestamp,
+    ) -> sqlx::Result<PackId> {
+        let result = sqlx::query(
+            r#"INSERT INTO "Pack"
+               ("packId", "changeLogDatabaseUuid", "changeLogId", "lastPackTime")
+               VALUES (?1, ?2, ?3, ?4)"#,
+        )
+        .bind(pack_uuid)
+        .bind(change_log_db_uuid)
+        .bind(change_log_id)
+        .bind(pack_time)
+        .execute(executor)
+        .await?;
+        debug_assert_eq!(result.rows_affected(), 1);
+        Ok(PackId::new(result.last_insert_rowid()))
+    }
+}
@@ -6,6 +6,20 @@ use sqlx::{FromRow, SqliteExecutor};
 
 use crate::TrackId;
 
+mod beat_data;
+pub use self::beat_data::BeatGridEntry;
+
+mod loops;
+pub use self::loops::LoopRegion;
+
+mod overview_waveform;
+pub use self::overview_waveform::WaveformSample;
+
+mod quick_cues;
+pub use self::quick_cues::CuePoint;
+
+mod reader;
+
 crate::db_id!(PerformanceDataId);
 
 #[derive(Debug, Clone, FromRow)]
@@ -22,6 +36,34 @@ pub struct PerformanceData {
 }
 
 impl PerformanceData {
+    /// Decodes the beat grid.
+    ///
+    /// See also [`BeatGridEntry::decode_beat_data()`].
+    pub fn decode_beat_data(&self) -> anyhow::Result<Vec<BeatGridEntry>> {
+        BeatGridEntry::decode_beat_data(&self.beat_data)
+    }
+
+    /// Decodes the hot cues.
+    ///
+    /// See also [`CuePoint::decode_quick_cues()`].
+    pub fn decode_quick_cues(&self) -> anyhow::Result<Vec<CuePoint>> {
+        CuePoint::decode_quick_cues(&self.quick_cues)
+    }
+
+    /// Decodes the overview waveform.
+    ///
+    /// See also [`WaveformSample::decode_overview_waveform()`].
+    pub fn decode_overview_waveform(&self) -> anyhow::Result<Vec<WaveformSample>> {
+        WaveformSample::decode_overview_waveform(&self.overview_wave_form_data)
+    }
+
+    /// Decodes the saved loops.
+    ///
+    /// See also [`LoopRegion::decode_loops()`].
+    pub fn decode_loops(&self) -> anyhow::Result<Vec<LoopRegion>> {
+        LoopRegion::decode_loops(&self.loops)
+    }
+
     /// Fetches all [`PerformanceData`] asynchronously.
     ///
     /// Unfiltered and in no particular order.
@@ -29,22 +71,115 @@ impl PerformanceData {
     pub fn fetch_all<'a>(
         executor: impl SqliteExecutor<'a> + 'a,
     ) -> BoxStream<'a, sqlx::Result<Self>> {
-        sqlx::query_as(r#"SELECT * FROM "PerformanceData" ORDER BY "id""#).fetch(executor)
+        sqlx::query_as(r#"SELECT * FROM "PerformanceData" ORDER BY "trackId""#).fetch(executor)
+    }
+
+    /// Counts all [`PerformanceData`].
+    pub async fn count_all(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
+        let count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "PerformanceData""#)
+            .fetch_one(executor)
+            .await?;
+        debug_assert!(count >= 0);
+        Ok(count.cast_unsigned())
+    }
+
+    /// Fetches the ids of all tracks with [`PerformanceData`] asynchronously.
+    ///
+    /// Ordered by track ID.
+    #[must_use]
+    pub fn find_track_ids_with_data<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+    ) -> BoxStream<'a, sqlx::Result<TrackId>> {
+        sqlx::query_scalar(r#"SELECT DISTINCT "trackId" FROM "PerformanceData" ORDER BY "trackId""#)
+            .fetch(executor)
     }
 
     /// Loads a single [`PerformanceData`] by ID.
     ///
+    /// The ID is the same as the ID of the corresponding track, see also
+    /// [`Self::try_load_by_track_id()`].
+    ///
     /// Returns `Ok(None)` if the requested [`PerformanceData`] has not been found.
     pub async fn try_load(
         executor: impl SqliteExecutor<'_>,
         id: PerformanceDataId,
     ) -> sqlx::Result<Option<Self>> {
-        sqlx::query_as(r#"SELECT * FROM "PerformanceData" WHERE "id"=?1"#)
-            .bind(id)
+        Self::try_load_by_track_id(executor, TrackId::new(id.to_raw())).await
+    }
+
+    /// Loads a single [`PerformanceData`] by track ID.
+    ///
+    /// Returns `Ok(None)` if the requested [`PerformanceData`] has not been found.
+    pub async fn try_load_by_track_id(
+        executor: impl SqliteExecutor<'_>,
+        track_id: TrackId,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "PerformanceData" WHERE "trackId"=?1"#)
+            .bind(track_id)
             .fetch_optional(executor)
             .await
     }
 
+    /// Inserts an empty record for a track.
+    ///
+    /// All binary data is empty. Engine DJ fills in the data when
+    /// analyzing the track.
+    ///
+    /// Fails if the track already has a record, see also [`Self::upsert()`].
+    pub async fn insert(executor: impl SqliteExecutor<'_>, track_id: TrackId) -> sqlx::Result<()> {
+        let result = sqlx::query(
+            r#"INSERT INTO "PerformanceData"
+               ("trackId", "trackData", "overviewWaveFormData", "beatData", "quickCues", "loops", "thirdPartySourceId", "activeOnLoadLoops")
+               VALUES (?1, x'', x'', x'', x'', x'', NULL, 0)"#,
+        )
+        .bind(track_id)
+        .execute(executor)
+        .await?;
+        debug_assert_eq!(result.rows_affected(), 1);
+        Ok(())
+    }
+
+    /// Inserts or replaces the record of a track with an empty record.
+    ///
+    /// Like [`Self::insert()`], but replaces an existing record.
+    pub async fn upsert(executor: impl SqliteExecutor<'_>, track_id: TrackId) -> sqlx::Result<()> {
+        let result = sqlx::query(
+            r#"INSERT OR REPLACE INTO "PerformanceData"
+               ("trackId", "trackData", "overviewWaveFormData", "beatData", "quickCues", "loops", "thirdPartySourceId", "activeOnLoadLoops")
+               VALUES (?1, x'', x'', x'', x'', x'', NULL, 0)"#,
+        )
+        .bind(track_id)
+        .execute(executor)
+        .await?;
+        debug_assert!(result.rows_affected() >= 1);
+        Ok(())
+    }
+
+    /// Deletes the record of a track.
+    ///
+    /// Returns `true` if the record has been deleted and `false`
+    /// if it has not been found.
+    pub async fn delete(
+        executor: impl SqliteExecutor<'_>,
+        track_id: TrackId,
+    ) -> sqlx::Result<bool> {
+        sqlx::query(r#"DELETE FROM "PerformanceData" WHERE "trackId"=?1"#)
+            .bind(track_id)
+            .execute(executor)
+            .await
+            .map(|result| {
+                debug_assert!(result.rows_affected() <= 1);
+                result.rows_affected() > 0
+            })
+    }
+
+    /// Resets the record of a track for re-analysis.
+    ///
+    /// Replaces the record with an empty placeholder, see also [`Self::upsert()`].
+    pub async fn reset(executor: impl SqliteExecutor<'_>, track_id: TrackId) -> sqlx::Result<()> {
+        Self::upsert(executor, track_id).await
+    }
+
     /// Delete all records with no associated track.
     pub async fn delete_orphaned(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
         let result = sqlx::query(
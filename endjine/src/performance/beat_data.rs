@@ -0,0 +1,195 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use super::reader::{Reader, compress, decompress};
+
+/// Marker in the beat grid.
+///
+/// Decoded from the `beatData` column of `PerformanceData`.
+///
+/// The tempo between two adjacent markers follows from the number of beats
+/// and the number of samples between them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeatGridEntry {
+    /// Position in the audio stream, measured in samples.
+    pub sample_offset: f64,
+
+    /// Number of the beat at this marker.
+    ///
+    /// Beats before the first downbeat have negative numbers.
+    pub beat_number: i64,
+
+    /// Number of beats until the next marker.
+    pub beat_count: i32,
+}
+
+impl BeatGridEntry {
+    /// Decodes the binary beat data.
+    ///
+    /// Returns the markers of the adjusted beat grid, i.e. the beat grid
+    /// that is actually used by Engine DJ.
+    ///
+    /// The data is compressed with `qCompress()`. The decompressed data
+    /// starts with the sample rate (`f64`, big-endian), the number of samples
+    /// (`f64`, big-endian), and a flag if the beat grid is set (`u8`). It is
+    /// followed by the default beat grid and the adjusted beat grid. Each
+    /// beat grid starts with the number of markers (`i64`, big-endian).
+    /// Each marker consists of the sample offset (`f64`), the beat number
+    /// (`i64`), the number of beats until the next marker (`i32`), and an
+    /// unknown value (`i32`), all in little-endian byte order.
+    ///
+    /// The layout follows the reverse-engineered format of Engine DJ 2.x
+    /// as implemented by [libdjinterop](https://github.com/xsco/libdjinterop).
+    pub fn decode_beat_data(beat_data: &[u8]) -> anyhow::Result<Vec<Self>> {
+        let data = decompress(beat_data)?;
+        let mut reader = Reader::new(&data);
+        let _sample_rate = reader.read_f64_be()?;
+        let _sample_count = reader.read_f64_be()?;
+        let _is_beat_grid_set = reader.read_bool()?;
+        let _default_beat_grid = read_beat_grid(&mut reader)?;
+        let adjusted_beat_grid = read_beat_grid(&mut reader)?;
+        reader.finish()?;
+        Ok(adjusted_beat_grid)
+    }
+
+    /// Encodes the binary beat data.
+    ///
+    /// Inverse of [`Self::decode_beat_data()`]. The markers are used both
+    /// for the default and the adjusted beat grid. The unknown value of
+    /// each marker is written as 0.
+    pub fn encode_beat_data(
+        sample_rate: f64,
+        sample_count: f64,
+        entries: &[Self],
+    ) -> anyhow::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&sample_rate.to_be_bytes());
+        data.extend_from_slice(&sample_count.to_be_bytes());
+        data.push((!entries.is_empty()).into());
+        // Default beat grid
+        write_beat_grid(&mut data, entries)?;
+        // Adjusted beat grid
+        write_beat_grid(&mut data, entries)?;
+        compress(&data)
+    }
+}
+
+fn read_beat_grid(reader: &mut Reader<'_>) -> anyhow::Result<Vec<BeatGridEntry>> {
+    let count = reader.read_count_be()?;
+    // The count is not trusted for preallocating memory.
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let sample_offset = reader.read_f64_le()?;
+        let beat_number = reader.read_i64_le()?;
+        let beat_count = reader.read_i32_le()?;
+        let _unknown = reader.read_i32_le()?;
+        entries.push(BeatGridEntry {
+            sample_offset,
+            beat_number,
+            beat_count,
+        });
+    }
+    Ok(entries)
+}
+
+fn write_beat_grid(data: &mut Vec<u8>, entries: &[BeatGridEntry]) -> anyhow::Result<()> {
+    data.extend_from_slice(&i64::try_from(entries.len())?.to_be_bytes());
+    for entry in entries {
+        let BeatGridEntry {
+            sample_offset,
+            beat_number,
+            beat_count,
+        } = entry;
+        data.extend_from_slice(&sample_offset.to_le_bytes());
+        data.extend_from_slice(&beat_number.to_le_bytes());
+        data.extend_from_slice(&beat_count.to_le_bytes());
+        data.extend_from_slice(&0i32.to_le_bytes());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BeatGridEntry;
+    use crate::performance::reader::compress;
+
+    /// Beat data of a 5 minute track with 44.1 kHz and a constant tempo
+    /// of 120 BPM. The first beat is at the start of the track.
+    ///
+    /// The adjusted beat grid is shifted by 100 samples.
+    fn fixture() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&44_100.0f64.to_be_bytes());
+        data.extend_from_slice(&13_230_000.0f64.to_be_bytes());
+        data.push(1);
+        for shift in [0.0, 100.0] {
+            data.extend_from_slice(&2i64.to_be_bytes());
+            for (sample_offset, beat_number, beat_count) in
+                [(-22_050.0f64, -1i64, 600i32), (13_207_950.0, 599, 0)]
+            {
+                data.extend_from_slice(&(sample_offset + shift).to_le_bytes());
+                data.extend_from_slice(&beat_number.to_le_bytes());
+                data.extend_from_slice(&beat_count.to_le_bytes());
+                data.extend_from_slice(&0i32.to_le_bytes());
+            }
+        }
+        compress(&data).unwrap()
+    }
+
+    #[test]
+    fn decode_empty() {
+        assert!(BeatGridEntry::decode_beat_data(&[]).is_err());
+    }
+
+    #[test]
+    fn decode_truncated() {
+        let beat_data = fixture();
+        assert!(BeatGridEntry::decode_beat_data(&beat_data[..beat_data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn decode_fixture() {
+        assert_eq!(
+            BeatGridEntry::decode_beat_data(&fixture()).unwrap(),
+            [
+                BeatGridEntry {
+                    sample_offset: -21_950.0,
+                    beat_number: -1,
+                    beat_count: 600,
+                },
+                BeatGridEntry {
+                    sample_offset: 13_208_050.0,
+                    beat_number: 599,
+                    beat_count: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let entries = [
+            BeatGridEntry {
+                sample_offset: -1_234.5,
+                beat_number: -4,
+                beat_count: 128,
+            },
+            BeatGridEntry {
+                sample_offset: 9_876_543.0,
+                beat_number: 124,
+                beat_count: 0,
+            },
+        ];
+        let beat_data = BeatGridEntry::encode_beat_data(44_100.0, 10_000_000.0, &entries).unwrap();
+        assert_eq!(
+            BeatGridEntry::decode_beat_data(&beat_data).unwrap(),
+            entries
+        );
+        let beat_data = BeatGridEntry::encode_beat_data(44_100.0, 0.0, &[]).unwrap();
+        assert!(
+            BeatGridEntry::decode_beat_data(&beat_data)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
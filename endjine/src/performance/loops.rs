@@ -0,0 +1,211 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use anyhow::bail;
+
+use super::reader::{Reader, write_short_string};
+
+/// Number of saved loop slots.
+const SLOT_COUNT: u8 = 8;
+
+/// Saved loop.
+///
+/// Decoded from the `loops` column of `PerformanceData`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopRegion {
+    /// Slot index.
+    pub index: u8,
+
+    /// Start position in the audio stream, measured in samples.
+    pub start_sample: f64,
+
+    /// End position in the audio stream, measured in samples.
+    pub end_sample: f64,
+
+    /// User-defined label.
+    pub label: String,
+
+    /// RGB color.
+    pub color: [u8; 3],
+}
+
+impl LoopRegion {
+    /// Decodes the binary loops data.
+    ///
+    /// Unlike the other performance data the loops data is not compressed.
+    /// It starts with the number of slots (`i64`). Each slot consists of the
+    /// UTF-8 label prefixed by its length in bytes (`u8`), the start and end
+    /// sample positions (`f64`), a flag if the start is set (`u8`), a flag
+    /// if the end is set (`u8`), and the ARGB color (4 x `u8`). All numbers
+    /// are encoded in little-endian byte order. Slots without both a start
+    /// and an end are skipped.
+    ///
+    /// The layout follows the reverse-engineered format of Engine DJ 2.x
+    /// as implemented by [libdjinterop](https://github.com/xsco/libdjinterop).
+    pub fn decode_loops(loops: &[u8]) -> anyhow::Result<Vec<Self>> {
+        let mut reader = Reader::new(loops);
+        let count = reader.read_count_le()?;
+        let mut regions = Vec::new();
+        for index in 0..count {
+            let label = reader.read_short_string()?;
+            let start_sample = reader.read_f64_le()?;
+            let end_sample = reader.read_f64_le()?;
+            let is_start_set = reader.read_bool()?;
+            let is_end_set = reader.read_bool()?;
+            let [_alpha, red, green, blue] = reader.read_array()?;
+            if !is_start_set || !is_end_set {
+                continue;
+            }
+            regions.push(Self {
+                index: index.try_into()?,
+                start_sample,
+                end_sample,
+                label,
+                color: [red, green, blue],
+            });
+        }
+        reader.finish()?;
+        Ok(regions)
+    }
+
+    /// Encodes the binary loops data.
+    ///
+    /// Inverse of [`Self::decode_loops()`]. All slots without a loop
+    /// are written as unused. Labels that exceed the maximum length
+    /// of 255 bytes are truncated.
+    ///
+    /// Fails if a slot index is out of range or occupied by multiple loops.
+    pub fn encode_loops(regions: &[Self]) -> anyhow::Result<Vec<u8>> {
+        let mut slots = [None; SLOT_COUNT as usize];
+        for region in regions {
+            let Some(slot) = slots.get_mut(usize::from(region.index)) else {
+                bail!("invalid loop index {index}", index = region.index);
+            };
+            if slot.replace(region).is_some() {
+                bail!("duplicate loop index {index}", index = region.index);
+            }
+        }
+        let mut data = Vec::new();
+        data.extend_from_slice(&i64::from(SLOT_COUNT).to_le_bytes());
+        for slot in slots {
+            if let Some(region) = slot {
+                let Self {
+                    index: _,
+                    start_sample,
+                    end_sample,
+                    label,
+                    color: [red, green, blue],
+                } = region;
+                write_short_string(&mut data, label);
+                data.extend_from_slice(&start_sample.to_le_bytes());
+                data.extend_from_slice(&end_sample.to_le_bytes());
+                data.extend_from_slice(&[1, 1, u8::MAX, *red, *green, *blue]);
+            } else {
+                write_short_string(&mut data, "");
+                data.extend_from_slice(&(-1.0f64).to_le_bytes());
+                data.extend_from_slice(&(-1.0f64).to_le_bytes());
+                data.extend_from_slice(&[0; 6]);
+            }
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoopRegion;
+
+    /// Loops with 3 slots, the second of which is unused.
+    const FIXTURE: [u8; 85] = [
+        0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x42, 0x75, 0x69, 0x6c, 0x64, 0x2d,
+        0x75, 0x70, 0x00, 0x00, 0x00, 0x00, 0x80, 0x88, 0xe5, 0x40, 0x00, 0x00, 0x00, 0x00, 0x80,
+        0x88, 0xf5, 0x40, 0x01, 0x01, 0xff, 0x12, 0x34, 0x56, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0xf0, 0xbf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf0, 0xbf, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x80, 0x84, 0x2e, 0x41, 0x00, 0x00, 0x00, 0xc0,
+        0xe0, 0xc8, 0x30, 0x41, 0x01, 0x01, 0xff, 0x00, 0xff, 0x00,
+    ];
+
+    #[test]
+    fn decode_empty() {
+        assert!(LoopRegion::decode_loops(&[]).is_err());
+        assert!(LoopRegion::decode_loops(&[0; 8]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn decode_truncated() {
+        assert!(LoopRegion::decode_loops(&FIXTURE[..FIXTURE.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn decode_invalid_flag() {
+        let mut loops = FIXTURE;
+        // The start flag of the first slot follows the count, the label,
+        // and the start and end positions.
+        loops[8 + 1 + 8 + 8 + 8] = 2;
+        assert!(LoopRegion::decode_loops(&loops).is_err());
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let regions = [
+            LoopRegion {
+                index: 1,
+                start_sample: 44_100.0,
+                end_sample: 88_200.0,
+                label: "Build-up".to_owned(),
+                color: [0x12, 0x34, 0x56],
+            },
+            LoopRegion {
+                index: 7,
+                start_sample: 1_000_000.25,
+                end_sample: 1_100_000.75,
+                label: String::new(),
+                color: [0x00, 0xff, 0x00],
+            },
+        ];
+        let loops = LoopRegion::encode_loops(&regions).unwrap();
+        assert_eq!(LoopRegion::decode_loops(&loops).unwrap(), regions);
+    }
+
+    #[test]
+    fn encode_invalid_index() {
+        let region = LoopRegion {
+            index: 0,
+            start_sample: 0.0,
+            end_sample: 1.0,
+            label: String::new(),
+            color: [0; 3],
+        };
+        assert!(
+            LoopRegion::encode_loops(&[LoopRegion {
+                index: 8,
+                ..region.clone()
+            }])
+            .is_err()
+        );
+        assert!(LoopRegion::encode_loops(&[region.clone(), region]).is_err());
+    }
+
+    #[test]
+    fn decode_fixture() {
+        assert_eq!(
+            LoopRegion::decode_loops(&FIXTURE).unwrap(),
+            [
+                LoopRegion {
+                    index: 0,
+                    start_sample: 44_100.0,
+                    end_sample: 88_200.0,
+                    label: "Build-up".to_owned(),
+                    color: [0x12, 0x34, 0x56],
+                },
+                LoopRegion {
+                    index: 2,
+                    start_sample: 1_000_000.25,
+                    end_sample: 1_100_000.75,
+                    label: String::new(),
+                    color: [0x00, 0xff, 0x00],
+                },
+            ]
+        );
+    }
+}
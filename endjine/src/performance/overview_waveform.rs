@@ -0,0 +1,115 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use anyhow::bail;
+
+use super::reader::{Reader, decompress};
+
+/// Size of an encoded [`WaveformSample`] in bytes.
+const ENCODED_SIZE: usize = 3;
+
+/// Sample of the downsampled overview waveform.
+///
+/// Decoded from the `overviewWaveFormData` column of `PerformanceData`.
+///
+/// All values are normalized to the range `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaveformSample {
+    /// Amplitude of the low frequency band.
+    pub low: f32,
+
+    /// Amplitude of the mid frequency band.
+    pub mid: f32,
+
+    /// Amplitude of the high frequency band.
+    pub high: f32,
+}
+
+impl WaveformSample {
+    /// Decodes the binary overview waveform data.
+    ///
+    /// The data is compressed with `qCompress()`. The decompressed data
+    /// starts with a header that contains the number of samples twice
+    /// (`i64`) and the number of audio samples per waveform sample (`f64`),
+    /// all big-endian. Each sample consists of the amplitudes of the low,
+    /// mid, and high frequency bands, each encoded as an unsigned byte (`u8`).
+    /// The samples are followed by the maximum amplitude of each band.
+    ///
+    /// The layout follows the reverse-engineered format of Engine DJ 2.x
+    /// as implemented by [libdjinterop](https://github.com/xsco/libdjinterop).
+    pub fn decode_overview_waveform(overview_waveform_data: &[u8]) -> anyhow::Result<Vec<Self>> {
+        let data = decompress(overview_waveform_data)?;
+        let mut reader = Reader::new(&data);
+        let count = reader.read_count_be()?;
+        let repeated_count = reader.read_count_be()?;
+        if count != repeated_count {
+            bail!("mismatching number of samples: {count} != {repeated_count}");
+        }
+        let _samples_per_entry = reader.read_f64_be()?;
+        let samples = reader.read_bytes(count.saturating_mul(ENCODED_SIZE))?;
+        let _max_sample = reader.read_array::<ENCODED_SIZE>()?;
+        reader.finish()?;
+        let samples = samples
+            .chunks_exact(ENCODED_SIZE)
+            .map(|record| Self {
+                low: normalize(record[0]),
+                mid: normalize(record[1]),
+                high: normalize(record[2]),
+            })
+            .collect();
+        Ok(samples)
+    }
+}
+
+#[must_use]
+fn normalize(value: u8) -> f32 {
+    f32::from(value) / f32::from(u8::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WaveformSample;
+
+    /// Overview waveform with 3 samples and 1024 audio samples per entry.
+    const FIXTURE: [u8; 33] = [
+        0x00, 0x00, 0x00, 0x24, 0x78, 0x9c, 0x63, 0x60, 0x00, 0x03, 0x66, 0x06, 0x28, 0xed, 0x30,
+        0x81, 0x01, 0x06, 0xfe, 0x33, 0x30, 0x18, 0xa7, 0xcd, 0xfc, 0x9f, 0x36, 0x13, 0x00, 0x23,
+        0x57, 0x05, 0x06,
+    ];
+
+    #[test]
+    fn decode_uncompressed() {
+        assert!(WaveformSample::decode_overview_waveform(&[]).is_err());
+        assert!(WaveformSample::decode_overview_waveform(&[0; 12]).is_err());
+    }
+
+    #[test]
+    fn decode_truncated() {
+        assert!(WaveformSample::decode_overview_waveform(&FIXTURE[..FIXTURE.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn decode_fixture() {
+        let samples = WaveformSample::decode_overview_waveform(&FIXTURE).unwrap();
+        assert_eq!(
+            samples,
+            [
+                WaveformSample {
+                    low: 0.0,
+                    mid: 0.0,
+                    high: 0.0,
+                },
+                WaveformSample {
+                    low: 1.0,
+                    mid: 0.0,
+                    high: 0.0,
+                },
+                WaveformSample {
+                    low: 0.2,
+                    mid: 0.4,
+                    high: 0.6,
+                },
+            ]
+        );
+    }
+}
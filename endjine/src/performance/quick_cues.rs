@@ -0,0 +1,196 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use anyhow::bail;
+
+use super::reader::{Reader, compress, decompress, write_short_string};
+
+/// Number of hot cue slots.
+const SLOT_COUNT: u8 = 8;
+
+/// Hot cue.
+///
+/// Decoded from the `quickCues` column of `PerformanceData`.
+///
+/// Saved loops are stored separately, see [`LoopRegion`](super::LoopRegion).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CuePoint {
+    /// Slot index.
+    pub index: u8,
+
+    /// Position in the audio stream, measured in samples.
+    pub sample_offset: f64,
+
+    /// User-defined label.
+    pub label: String,
+
+    /// RGB color.
+    pub color: [u8; 3],
+}
+
+impl CuePoint {
+    /// Decodes the binary quick cues data.
+    ///
+    /// The data is compressed with `qCompress()`. The decompressed data
+    /// starts with the number of slots (`i64`). Each slot consists of the
+    /// UTF-8 label prefixed by its length in bytes (`u8`), the sample
+    /// offset (`f64`), and the ARGB color (4 x `u8`). Unused slots have a
+    /// negative sample offset and are skipped. The slots are followed by
+    /// the adjusted main cue (`f64`), a flag if the main cue has been
+    /// adjusted (`u8`), and the default main cue (`f64`). All numbers are
+    /// encoded in big-endian byte order.
+    ///
+    /// The layout follows the reverse-engineered format of Engine DJ 2.x
+    /// as implemented by [libdjinterop](https://github.com/xsco/libdjinterop).
+    pub fn decode_quick_cues(quick_cues: &[u8]) -> anyhow::Result<Vec<Self>> {
+        let data = decompress(quick_cues)?;
+        let mut reader = Reader::new(&data);
+        let count = reader.read_count_be()?;
+        let mut cues = Vec::new();
+        for index in 0..count {
+            let label = reader.read_short_string()?;
+            let sample_offset = reader.read_f64_be()?;
+            let [_alpha, red, green, blue] = reader.read_array()?;
+            if sample_offset < 0.0 {
+                continue;
+            }
+            cues.push(Self {
+                index: index.try_into()?,
+                sample_offset,
+                label,
+                color: [red, green, blue],
+            });
+        }
+        let _adjusted_main_cue = reader.read_f64_be()?;
+        let _is_main_cue_adjusted = reader.read_bool()?;
+        let _default_main_cue = reader.read_f64_be()?;
+        reader.finish()?;
+        Ok(cues)
+    }
+
+    /// Encodes the binary quick cues data.
+    ///
+    /// Inverse of [`Self::decode_quick_cues()`]. All slots without a cue
+    /// are written as unused. The main cue is set to the start of the track.
+    /// Labels that exceed the maximum length of 255 bytes are truncated.
+    ///
+    /// Fails if a slot index is out of range or occupied by multiple cues.
+    pub fn encode_quick_cues(cues: &[Self]) -> anyhow::Result<Vec<u8>> {
+        let mut slots = [None; SLOT_COUNT as usize];
+        for cue in cues {
+            let Some(slot) = slots.get_mut(usize::from(cue.index)) else {
+                bail!("invalid cue index {index}", index = cue.index);
+            };
+            if slot.replace(cue).is_some() {
+                bail!("duplicate cue index {index}", index = cue.index);
+            }
+        }
+        let mut data = Vec::new();
+        data.extend_from_slice(&i64::from(SLOT_COUNT).to_be_bytes());
+        for slot in slots {
+            if let Some(cue) = slot {
+                let Self {
+                    index: _,
+                    sample_offset,
+                    label,
+                    color: [red, green, blue],
+                } = cue;
+                write_short_string(&mut data, label);
+                data.extend_from_slice(&sample_offset.to_be_bytes());
+                data.extend_from_slice(&[u8::MAX, *red, *green, *blue]);
+            } else {
+                write_short_string(&mut data, "");
+                data.extend_from_slice(&(-1.0f64).to_be_bytes());
+                data.extend_from_slice(&[0; 4]);
+            }
+        }
+        // Main cue: adjusted, is adjusted, default.
+        data.extend_from_slice(&0.0f64.to_be_bytes());
+        data.push(0);
+        data.extend_from_slice(&0.0f64.to_be_bytes());
+        compress(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CuePoint;
+
+    /// Quick cues with 3 slots, the second of which is unused.
+    const FIXTURE: [u8; 59] = [
+        0x00, 0x00, 0x00, 0x49, 0x78, 0x9c, 0x63, 0x60, 0x00, 0x03, 0x66, 0x56, 0xcf, 0xbc, 0x92,
+        0xa2, 0x7c, 0x87, 0xa7, 0x1d, 0x0d, 0x20, 0xee, 0xff, 0xff, 0x40, 0x62, 0xff, 0x07, 0x06,
+        0x38, 0x60, 0x71, 0x29, 0xca, 0x2f, 0x70, 0x34, 0xba, 0xd6, 0x0e, 0x92, 0xfe, 0x2f, 0x64,
+        0x12, 0xe6, 0x70, 0x15, 0xa2, 0x92, 0x11, 0xa6, 0x02, 0x00, 0x5f, 0x97, 0x0f, 0x91,
+    ];
+
+    #[test]
+    fn decode_uncompressed() {
+        assert!(CuePoint::decode_quick_cues(&[]).is_err());
+        assert!(CuePoint::decode_quick_cues(&[0; 8]).is_err());
+    }
+
+    #[test]
+    fn decode_truncated() {
+        assert!(CuePoint::decode_quick_cues(&FIXTURE[..FIXTURE.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let cues = [
+            CuePoint {
+                index: 0,
+                sample_offset: 44_100.0,
+                label: "Intro".to_owned(),
+                color: [0xff, 0x00, 0x00],
+            },
+            CuePoint {
+                index: 7,
+                sample_offset: 1_234_567.5,
+                label: String::new(),
+                color: [0x12, 0x34, 0x56],
+            },
+        ];
+        let quick_cues = CuePoint::encode_quick_cues(&cues).unwrap();
+        assert_eq!(CuePoint::decode_quick_cues(&quick_cues).unwrap(), cues);
+    }
+
+    #[test]
+    fn encode_invalid_index() {
+        let cue = CuePoint {
+            index: 0,
+            sample_offset: 0.0,
+            label: String::new(),
+            color: [0; 3],
+        };
+        assert!(
+            CuePoint::encode_quick_cues(&[CuePoint {
+                index: 8,
+                ..cue.clone()
+            }])
+            .is_err()
+        );
+        assert!(CuePoint::encode_quick_cues(&[cue.clone(), cue]).is_err());
+    }
+
+    #[test]
+    fn decode_fixture() {
+        assert_eq!(
+            CuePoint::decode_quick_cues(&FIXTURE).unwrap(),
+            [
+                CuePoint {
+                    index: 0,
+                    sample_offset: 44_100.0,
+                    label: "Intro".to_owned(),
+                    color: [0xff, 0x00, 0x00],
+                },
+                CuePoint {
+                    index: 2,
+                    sample_offset: 1_234_567.5,
+                    label: "Drop".to_owned(),
+                    color: [0x12, 0x34, 0x56],
+                },
+            ]
+        );
+    }
+}
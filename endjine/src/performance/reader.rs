@@ -0,0 +1,233 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::io::{Read as _, Write as _};
+
+use anyhow::{Context as _, bail};
+use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
+
+/// Maximum size of decompressed data.
+///
+/// Performance data only occupies a few KiB. The limit protects against
+/// corrupt or malicious size prefixes and zlib streams.
+const MAX_DECOMPRESSED_SIZE: u32 = 16 * 1024 * 1024;
+
+/// Upper bound of the compression ratio when preallocating the decompressed data.
+const MAX_PREALLOCATION_RATIO: usize = 16;
+
+/// Decompresses data that has been compressed with `qCompress()` of Qt.
+///
+/// The zlib stream is prefixed by the size of the uncompressed data
+/// (`u32`, big-endian). Fails if the size exceeds [`MAX_DECOMPRESSED_SIZE`]
+/// or does not match the size of the decompressed data.
+pub(super) fn decompress(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut reader = Reader::new(data);
+    let size = u32::from_be_bytes(reader.read_array()?);
+    if size > MAX_DECOMPRESSED_SIZE {
+        bail!("size of decompressed data exceeds limit: {size} byte(s)");
+    }
+    // The size prefix is not trusted for preallocating memory.
+    let capacity =
+        usize::try_from(size)?.min(reader.data.len().saturating_mul(MAX_PREALLOCATION_RATIO));
+    let mut decompressed = Vec::with_capacity(capacity);
+    // Reading a single excess byte is sufficient to detect a size mismatch.
+    ZlibDecoder::new(reader.data)
+        .take(u64::from(size) + 1)
+        .read_to_end(&mut decompressed)
+        .context("decompress data")?;
+    if decompressed.len() != usize::try_from(size)? {
+        bail!(
+            "unexpected size of decompressed data: expected {size} byte(s), found {len}",
+            len = decompressed.len()
+        );
+    }
+    Ok(decompressed)
+}
+
+/// Compresses data like `qCompress()` of Qt.
+///
+/// Inverse of [`decompress()`].
+pub(super) fn compress(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let size = u32::try_from(data.len()).context("data too large")?;
+    let mut encoder = ZlibEncoder::new(size.to_be_bytes().to_vec(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Reads values from binary data.
+#[derive(Debug)]
+pub(super) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    #[must_use]
+    pub(super) const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    #[must_use]
+    pub(super) const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub(super) fn read_bytes(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let Some((bytes, remaining)) = self.data.split_at_checked(len) else {
+            bail!(
+                "unexpected end of data: expected {len} byte(s), found {remaining}",
+                remaining = self.data.len()
+            );
+        };
+        self.data = remaining;
+        Ok(bytes)
+    }
+
+    pub(super) fn read_array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        let bytes = self.read_bytes(N)?;
+        Ok(bytes.try_into()?)
+    }
+
+    pub(super) fn read_u8(&mut self) -> anyhow::Result<u8> {
+        let [value] = self.read_array()?;
+        Ok(value)
+    }
+
+    pub(super) fn read_bool(&mut self) -> anyhow::Result<bool> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            invalid => bail!("invalid boolean value: {invalid}"),
+        }
+    }
+
+    pub(super) fn read_f64_le(&mut self) -> anyhow::Result<f64> {
+        self.read_array().map(f64::from_le_bytes)
+    }
+
+    pub(super) fn read_f64_be(&mut self) -> anyhow::Result<f64> {
+        self.read_array().map(f64::from_be_bytes)
+    }
+
+    pub(super) fn read_i32_le(&mut self) -> anyhow::Result<i32> {
+        self.read_array().map(i32::from_le_bytes)
+    }
+
+    pub(super) fn read_i64_le(&mut self) -> anyhow::Result<i64> {
+        self.read_array().map(i64::from_le_bytes)
+    }
+
+    pub(super) fn read_i64_be(&mut self) -> anyhow::Result<i64> {
+        self.read_array().map(i64::from_be_bytes)
+    }
+
+    /// Reads a non-negative count (`i64`, big-endian).
+    pub(super) fn read_count_be(&mut self) -> anyhow::Result<usize> {
+        let count = self.read_i64_be()?;
+        usize::try_from(count).with_context(|| format!("invalid count: {count}"))
+    }
+
+    /// Reads a non-negative count (`i64`, little-endian).
+    pub(super) fn read_count_le(&mut self) -> anyhow::Result<usize> {
+        let count = self.read_i64_le()?;
+        usize::try_from(count).with_context(|| format!("invalid count: {count}"))
+    }
+
+    /// Fails if not all data has been read.
+    pub(super) fn finish(self) -> anyhow::Result<()> {
+        if !self.is_empty() {
+            bail!(
+                "unexpected trailing data: {len} byte(s)",
+                len = self.data.len()
+            );
+        }
+        Ok(())
+    }
+
+    /// Reads a UTF-8 string prefixed by its length in bytes (`u8`).
+    pub(super) fn read_short_string(&mut self) -> anyhow::Result<String> {
+        let len = self.read_u8()?;
+        let bytes = self.read_bytes(len.into())?;
+        Ok(std::str::from_utf8(bytes)?.to_owned())
+    }
+}
+
+/// Writes a UTF-8 string prefixed by its length in bytes (`u8`).
+///
+/// Strings that exceed the maximum length are truncated at a character boundary.
+pub(super) fn write_short_string(data: &mut Vec<u8>, value: &str) {
+    let mut len = value.len().min(u8::MAX.into());
+    while !value.is_char_boundary(len) {
+        len -= 1;
+    }
+    let bytes = &value.as_bytes()[..len];
+    data.push(u8::try_from(bytes.len()).expect("truncated"));
+    data.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_DECOMPRESSED_SIZE, Reader, compress, decompress, write_short_string};
+
+    #[test]
+    fn decompress_qcompress_data() {
+        // qCompress("abcabcabc")
+        const COMPRESSED: [u8; 17] = [
+            0x00, 0x00, 0x00, 0x09, 0x78, 0x9c, 0x4b, 0x4c, 0x4a, 0x4e, 0x04, 0x23, 0x00, 0x11,
+            0x3d, 0x03, 0x73,
+        ];
+        assert_eq!(decompress(&COMPRESSED).unwrap(), b"abcabcabc");
+        let mut invalid_size = COMPRESSED;
+        invalid_size[3] = 0x0a;
+        assert!(decompress(&invalid_size).is_err());
+        assert!(decompress(&COMPRESSED[..COMPRESSED.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn decompress_untrusted_size() {
+        let mut compressed = compress(b"abcabcabc").unwrap();
+        // Exceeds the limit
+        compressed[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(decompress(&compressed).is_err());
+        // Within the limit, but larger than the actual size
+        compressed[..4].copy_from_slice(&MAX_DECOMPRESSED_SIZE.to_be_bytes());
+        assert!(decompress(&compressed).is_err());
+        // Smaller than the actual size
+        compressed[..4].copy_from_slice(&1u32.to_be_bytes());
+        assert!(decompress(&compressed).is_err());
+    }
+
+    #[test]
+    fn compress_decompress_round_trip() {
+        let data = b"abcabcabc".repeat(100);
+        assert_eq!(decompress(&compress(&data).unwrap()).unwrap(), data);
+        assert_eq!(decompress(&compress(&[]).unwrap()).unwrap(), b"");
+    }
+
+    #[test]
+    fn short_string_round_trip() {
+        let mut data = Vec::new();
+        write_short_string(&mut data, "");
+        write_short_string(&mut data, "Drop");
+        let mut reader = Reader::new(&data);
+        assert_eq!(reader.read_short_string().unwrap(), "");
+        assert_eq!(reader.read_short_string().unwrap(), "Drop");
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn short_string_truncated_at_char_boundary() {
+        let value = "ä".repeat(200);
+        let mut data = Vec::new();
+        write_short_string(&mut data, &value);
+        assert_eq!(data[0], 254);
+        let mut reader = Reader::new(&data);
+        assert_eq!(reader.read_short_string().unwrap(), "ä".repeat(127));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn unexpected_end_of_data() {
+        let mut reader = Reader::new(&[1, 2, 3]);
+        assert!(reader.read_f64_le().is_err());
+    }
+}
@@ -1,20 +1,26 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
-use std::borrow::{Borrow, Cow};
+use std::{
+    borrow::{Borrow, Cow},
+    collections::HashMap,
+};
 
 use anyhow::{Context as _, bail};
 use futures_util::{
-    StreamExt as _, TryStreamExt as _,
+    StreamExt as _, TryStreamExt as _, future,
     stream::{BoxStream, FuturesOrdered},
 };
 use itertools::Itertools;
 use sqlx::{
-    FromRow, SqliteExecutor, SqlitePool, sqlite::SqliteQueryResult, types::time::PrimitiveDateTime,
+    FromRow, Row as _, SqliteConnection, SqliteExecutor, SqlitePool,
+    sqlite::{SqliteQueryResult, SqliteRow},
+    types::time::PrimitiveDateTime,
 };
 
 use crate::{
-    DbUuid, FilePath, LibraryPath, OriginTrackRef, Track, TrackId, import_track_file_path,
+    DatabaseError, DatabaseResult, DbUuid, FilePath, LibraryPath, OriginTrackRef, Track, TrackId,
+    import_track_file_path, utc_now_primitive_date_time,
 };
 
 crate::db_id!(PlaylistId);
@@ -27,6 +33,16 @@ fn next_membership_reference(membership_reference: i64) -> i64 {
     membership_reference + 1
 }
 
+/// Controls how tracks are added to a playlist.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportPlaylistMode {
+    /// Appends tracks to a playlist.
+    #[default]
+    Append,
+    /// Replaces all tracks of a playlist.
+    Replace,
+}
+
 #[derive(Debug, Clone, FromRow)]
 #[sqlx(rename_all = "camelCase")]
 pub struct Playlist {
@@ -40,7 +56,41 @@ pub struct Playlist {
     pub is_explicitly_exported: bool,
 }
 
+/// A [`Playlist`] with all its descendants.
+#[derive(Debug, Clone)]
+pub struct PlaylistNode {
+    pub playlist: Playlist,
+    /// Ordered by the `nextListId` chain of the siblings.
+    pub children: Vec<PlaylistNode>,
+}
+
 impl Playlist {
+    /// Loads all [`Playlist`]s as a tree.
+    ///
+    /// Returns the root nodes. Siblings are ordered by following the
+    /// `nextListId` chain. Siblings that are not reachable through the
+    /// chain are appended in order of their ID.
+    ///
+    /// Playlists with a dangling or cyclic parent reference are omitted.
+    pub async fn fetch_hierarchy(
+        executor: impl SqliteExecutor<'_>,
+    ) -> sqlx::Result<Vec<PlaylistNode>> {
+        let playlists: Vec<Self> = sqlx::query_as(r#"SELECT * FROM "Playlist" ORDER BY "id""#)
+            .fetch_all(executor)
+            .await?;
+        let mut children_by_parent_id = HashMap::<_, Vec<_>>::new();
+        for playlist in playlists {
+            children_by_parent_id
+                .entry(playlist.parent_list_id)
+                .or_default()
+                .push(playlist);
+        }
+        Ok(build_playlist_nodes(
+            &mut children_by_parent_id,
+            PlaylistId::INVALID_ZERO,
+        ))
+    }
+
     /// Fetches all [`Playlist`]s.
     ///
     /// Unfiltered and in no particular order.
@@ -51,6 +101,15 @@ impl Playlist {
         sqlx::query_as(r#"SELECT * FROM "Playlist" ORDER BY "id""#).fetch(executor)
     }
 
+    /// Counts all [`Playlist`]s.
+    pub async fn count_all(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
+        let count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "Playlist""#)
+            .fetch_one(executor)
+            .await?;
+        debug_assert!(count >= 0);
+        Ok(count.cast_unsigned())
+    }
+
     /// Fetches all empty [`Playlist`]s without children.
     ///
     /// In no particular order.
@@ -79,6 +138,113 @@ impl Playlist {
             })
     }
 
+    /// Deletes a [`Playlist`] and all its entries.
+    ///
+    /// Without `recursive` fails if the [`Playlist`] has children or entries.
+    /// With `recursive` all descendants are deleted depth-first.
+    ///
+    /// Returns the number of deleted playlists and entries.
+    ///
+    /// Must run within a transaction in isolation.
+    pub async fn delete_tree(
+        connection: &mut SqliteConnection,
+        id: PlaylistId,
+        recursive: bool,
+    ) -> anyhow::Result<(u64, u64)> {
+        if Self::try_load(&mut *connection, id).await?.is_none() {
+            bail!("playlist {id} not found");
+        }
+        if !recursive {
+            let child_count: i64 =
+                sqlx::query_scalar(r#"SELECT COUNT(*) FROM "Playlist" WHERE "parentListId"=?1"#)
+                    .bind(id)
+                    .fetch_one(&mut *connection)
+                    .await?;
+            if child_count > 0 {
+                bail!("playlist {id} has {child_count} child(ren)");
+            }
+            let entry_count = PlaylistEntity::count_list(&mut *connection, id).await?;
+            if entry_count > 0 {
+                bail!("playlist {id} has {entry_count} entry(ies)");
+            }
+        }
+
+        // Maintain the linked list of siblings. Database triggers might already
+        // have done this, i.e. this update is idempotent.
+        sqlx::query(
+            r#"UPDATE "Playlist"
+               SET "nextListId"=(SELECT "nextListId" FROM "Playlist" WHERE "id"=?1)
+               WHERE "nextListId"=?1"#,
+        )
+        .bind(id)
+        .execute(&mut *connection)
+        .await?;
+
+        // Children are deleted before their parents.
+        let subtree_ids = sqlx::query_scalar::<_, PlaylistId>(
+            r#"WITH RECURSIVE "Subtree" ("id", "depth") AS (
+                SELECT ?1, 0
+                UNION ALL
+                SELECT "Playlist"."id", "Subtree"."depth"+1
+                FROM "Playlist" JOIN "Subtree" ON "Playlist"."parentListId"="Subtree"."id"
+            )
+            SELECT "id" FROM "Subtree" ORDER BY "depth" DESC, "id""#,
+        )
+        .bind(id)
+        .fetch_all(&mut *connection)
+        .await?;
+        debug_assert!(recursive || subtree_ids.len() == 1);
+        let mut deleted_playlist_count = 0;
+        let mut deleted_entry_count = 0;
+        for subtree_id in subtree_ids {
+            deleted_entry_count += PlaylistEntity::delete_list(&mut *connection, subtree_id)
+                .await?
+                .rows_affected();
+            deleted_playlist_count += sqlx::query(r#"DELETE FROM "Playlist" WHERE "id"=?1"#)
+                .bind(subtree_id)
+                .execute(&mut *connection)
+                .await?
+                .rows_affected();
+        }
+        Ok((deleted_playlist_count, deleted_entry_count))
+    }
+
+    /// Deletes a [`Playlist`] and all its entries atomically.
+    ///
+    /// Without `force` fails if the [`Playlist`] has children. With `force`
+    /// all descendants are deleted recursively, see [`Self::delete_tree()`].
+    ///
+    /// Returns the number of deleted entries and whether the [`Playlist`]
+    /// existed.
+    pub async fn delete_with_entries(
+        pool: &SqlitePool,
+        id: PlaylistId,
+        force: bool,
+    ) -> anyhow::Result<(u64, bool)> {
+        let mut tx = pool.begin().await?;
+        if Self::try_load(&mut *tx, id).await?.is_none() {
+            return Ok((0, false));
+        }
+        if !force {
+            let child_count: i64 =
+                sqlx::query_scalar(r#"SELECT COUNT(*) FROM "Playlist" WHERE "parentListId"=?1"#)
+                    .bind(id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            if child_count > 0 {
+                bail!("playlist {id} has {child_count} child(ren)");
+            }
+        }
+        let deleted_entry_count = PlaylistEntity::delete_list(&mut *tx, id)
+            .await?
+            .rows_affected();
+        let (deleted_playlist_count, deleted_descendant_entry_count) =
+            Self::delete_tree(&mut tx, id, force).await?;
+        debug_assert!(deleted_playlist_count >= 1);
+        tx.commit().await?;
+        Ok((deleted_entry_count + deleted_descendant_entry_count, true))
+    }
+
     /// Deletes all empty [`Playlist`]s without children.
     pub async fn delete_all_empty_without_children(
         executor: impl SqliteExecutor<'_>,
@@ -106,6 +272,151 @@ impl Playlist {
             .await
     }
 
+    /// Creates a new, empty [`Playlist`].
+    ///
+    /// The new [`Playlist`] is appended after the last child of the parent.
+    /// Use [`PlaylistId::INVALID_ZERO`] as `parent_list_id` for top-level
+    /// playlists.
+    ///
+    /// Returns the ID of the new [`Playlist`].
+    pub async fn create(
+        connection: &mut SqliteConnection,
+        parent_list_id: PlaylistId,
+        title: &str,
+        is_persisted: bool,
+    ) -> sqlx::Result<PlaylistId> {
+        if !is_valid_playlist_path_segment(title) {
+            return Err(sqlx::Error::InvalidArgument(format!(
+                "invalid playlist title \"{title}\""
+            )));
+        }
+        let query_result = sqlx::query(
+            r#"INSERT INTO "Playlist"
+               ("title", "parentListId", "isPersisted", "nextListId", "lastEditTime", "isExplicitlyExported")
+               VALUES (?1, ?2, ?3, ?4, ?5, TRUE)"#,
+        )
+        .bind(title)
+        .bind(parent_list_id)
+        .bind(is_persisted)
+        .bind(PlaylistId::INVALID_ZERO)
+        .bind(utc_now_primitive_date_time())
+        .execute(&mut *connection)
+        .await?;
+        debug_assert_eq!(query_result.rows_affected(), 1);
+        let id = PlaylistId::new(query_result.last_insert_rowid());
+
+        // Maintain the linked list of siblings. Database triggers might already
+        // have done this, i.e. this update is idempotent.
+        sqlx::query(
+            r#"UPDATE "Playlist"
+               SET "nextListId"=?1
+               WHERE "parentListId"=?2 AND "nextListId"=?3 AND "id"<>?1"#,
+        )
+        .bind(id)
+        .bind(parent_list_id)
+        .bind(PlaylistId::INVALID_ZERO)
+        .execute(&mut *connection)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Renames a [`Playlist`].
+    ///
+    /// Only changes the title, i.e. the last segment of the path.
+    ///
+    /// Returns `false` if the requested [`Playlist`] has not been found.
+    pub async fn rename(
+        executor: impl SqliteExecutor<'_>,
+        id: PlaylistId,
+        title: &str,
+    ) -> sqlx::Result<bool> {
+        if !is_valid_playlist_path_segment(title) {
+            return Err(sqlx::Error::InvalidArgument(format!(
+                "invalid playlist title \"{title}\""
+            )));
+        }
+        let result =
+            sqlx::query(r#"UPDATE "Playlist" SET "title"=?2, "lastEditTime"=?3 WHERE "id"=?1"#)
+                .bind(id)
+                .bind(title)
+                .bind(utc_now_primitive_date_time())
+                .execute(executor)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Creates a new, empty [`Playlist`] and all missing parents.
+    ///
+    /// The path is split into segments by [`PLAYLIST_PATH_SEGMENT_SEPARATOR`].
+    /// Fails if the [`Playlist`] already exists.
+    ///
+    /// Returns the ID of the new [`Playlist`].
+    ///
+    /// Must run within a transaction in isolation.
+    pub async fn create_path(
+        connection: &mut SqliteConnection,
+        path: &str,
+        is_persisted: bool,
+    ) -> anyhow::Result<PlaylistId> {
+        let (id, created) = Self::find_or_create_by_path(connection, path, is_persisted).await?;
+        if !created {
+            bail!("playlist \"{path}\" already exists");
+        }
+        Ok(id)
+    }
+
+    /// Finds an existing [`Playlist`] or creates a new, empty [`Playlist`].
+    ///
+    /// Like [`Self::create_path()`], all missing parents are created.
+    ///
+    /// Returns the ID of the [`Playlist`] and `true` if it has been created.
+    ///
+    /// Must run within a transaction in isolation.
+    pub async fn find_or_create_by_path(
+        connection: &mut SqliteConnection,
+        path: &str,
+        is_persisted: bool,
+    ) -> anyhow::Result<(PlaylistId, bool)> {
+        let segments = split_playlist_path_into_segments(path)?;
+        let Some((title, parent_segments)) = segments.split_last() else {
+            bail!("empty playlist path");
+        };
+        let mut parent_list_id = PlaylistId::INVALID_ZERO;
+        for segment in parent_segments {
+            parent_list_id = if let Some(id) =
+                Self::find_id_by_parent_and_title(&mut *connection, parent_list_id, segment).await?
+            {
+                id
+            } else {
+                Self::create(&mut *connection, parent_list_id, segment, is_persisted).await?
+            };
+        }
+        if let Some(id) =
+            Self::find_id_by_parent_and_title(&mut *connection, parent_list_id, title).await?
+        {
+            return Ok((id, false));
+        }
+        let id = Self::create(connection, parent_list_id, title, is_persisted).await?;
+        Ok((id, true))
+    }
+
+    /// Finds a [`Playlist`] by its parent and title.
+    ///
+    /// Use [`PlaylistId::INVALID_ZERO`] as `parent_list_id` for top-level
+    /// playlists.
+    pub async fn find_id_by_parent_and_title(
+        executor: impl SqliteExecutor<'_>,
+        parent_list_id: PlaylistId,
+        title: &str,
+    ) -> sqlx::Result<Option<PlaylistId>> {
+        sqlx::query_scalar(r#"SELECT "id" FROM "Playlist" WHERE "parentListId"=?1 AND "title"=?2"#)
+            .bind(parent_list_id)
+            .bind(title)
+            .fetch_optional(executor)
+            .await
+    }
+
     pub async fn find_id_by_path(
         executor: impl SqliteExecutor<'_>,
         path: &str,
@@ -127,15 +438,12 @@ impl Playlist {
     /// Returns all duplicate tracks that have been ignored.
     ///
     /// Must run within a transaction in isolation.
-    pub async fn append_tracks<'e, E>(
-        mut executor: impl FnMut() -> E,
+    pub async fn append_tracks(
+        connection: &mut SqliteConnection,
         id: PlaylistId,
         track_refs: impl IntoIterator<Item = OriginTrackRef>,
-    ) -> anyhow::Result<Vec<OriginTrackRef>>
-    where
-        E: SqliteExecutor<'e>,
-    {
-        let last_entity = PlaylistEntity::try_load_last_of_list(&mut executor, id).await?;
+    ) -> anyhow::Result<Vec<OriginTrackRef>> {
+        let last_entity = PlaylistEntity::try_load_last_of_list(&mut *connection, id).await?;
 
         let (mut prev_entity_id, mut next_membership_ref) = last_entity.map_or(
             (PlaylistEntityId::INVALID_ZERO, MIN_MEMBERSHIP_REFERENCE),
@@ -163,7 +471,7 @@ impl Playlist {
             .bind(db_uuid)
             .bind(PlaylistEntityId::INVALID_ZERO)
             .bind(next_membership_ref)
-            .execute(executor())
+            .execute(&mut *connection)
             .await?;
 
             debug_assert!(query_result.rows_affected() <= 1);
@@ -181,7 +489,7 @@ impl Playlist {
                 sqlx::query(r#"UPDATE "PlaylistEntity" SET "nextEntityId"=?1 WHERE "id"=?2"#)
                     .bind(new_entity_id)
                     .bind(prev_entity_id)
-                    .execute(executor())
+                    .execute(&mut *connection)
                     .await?;
             }
 
@@ -193,6 +501,46 @@ impl Playlist {
         Ok(ignored_track_refs)
     }
 
+    /// Merges the tracks of a source playlist into a target playlist.
+    ///
+    /// The tracks of the source playlist are appended to the target
+    /// playlist in order. Tracks that are already contained in the
+    /// target playlist are skipped.
+    ///
+    /// With `delete_source` the source playlist is deleted afterwards.
+    /// Fails if the source playlist has children.
+    ///
+    /// Returns the number of appended and skipped tracks.
+    ///
+    /// Must run within a transaction in isolation.
+    pub async fn merge(
+        connection: &mut SqliteConnection,
+        source_id: PlaylistId,
+        target_id: PlaylistId,
+        delete_source: bool,
+    ) -> anyhow::Result<(u64, u64)> {
+        if source_id == target_id {
+            bail!("cannot merge playlist {source_id} into itself");
+        }
+        if Self::try_load(&mut *connection, target_id).await?.is_none() {
+            bail!("playlist {target_id} not found");
+        }
+        let source_entries = PlaylistEntity::load_list(&mut *connection, source_id).await?;
+        let track_count = source_entries.len() as u64;
+        let skipped_track_refs = Self::append_tracks(
+            &mut *connection,
+            target_id,
+            source_entries.iter().map(PlaylistEntity::track_ref),
+        )
+        .await?;
+        let skipped_count = skipped_track_refs.len() as u64;
+        if delete_source {
+            PlaylistEntity::delete_list(&mut *connection, source_id).await?;
+            Self::delete_tree(connection, source_id, false).await?;
+        }
+        Ok((track_count - skipped_count, skipped_count))
+    }
+
     /// Replaces all tracks in a playlist.
     ///
     /// This method replaces all existing tracks in the playlist.
@@ -201,18 +549,17 @@ impl Playlist {
     /// Returns all duplicate tracks that have been ignored.
     ///
     /// Must run within a transaction in isolation.
-    pub async fn replace_tracks<'e, E>(
-        mut executor: impl FnMut() -> E,
+    pub async fn replace_tracks(
+        connection: &mut SqliteConnection,
         id: PlaylistId,
         track_refs: impl IntoIterator<Item = OriginTrackRef>,
-    ) -> anyhow::Result<Vec<OriginTrackRef>>
-    where
-        E: SqliteExecutor<'e>,
-    {
-        let mut existing_entries = PlaylistEntity::load_list(executor(), id).await?.into_iter();
-        let mut track_refs = track_refs.into_iter();
-        let mut last_entry_id = None;
-        let mut ignored_track_refs = Vec::new();
+    ) -> anyhow::Result<Vec<OriginTrackRef>> {
+        // All existing entries need to be updated after their track ids
+        // have been negated below.
+        let mut existing_entries = PlaylistEntity::load_list(&mut *connection, id)
+            .await?
+            .into_iter()
+            .peekable();
 
         // Prevent uniqueness constraint violations between replaced and
         // remaining existing entries. This works, because the trackId
@@ -220,22 +567,26 @@ impl Playlist {
         let query_result = sqlx::query(
             r#"UPDATE "PlaylistEntity"
                     SET "trackId"=-"trackId"
-                    WHERE "id"=?1 AND "trackId">0"#,
+                    WHERE "listId"=?1 AND "trackId">0"#,
         )
         .bind(id)
-        .execute(executor())
+        .execute(&mut *connection)
         .await?;
         log::debug!(
             "Replacing {} entry(ies) in playlist {id}",
             query_result.rows_affected()
         );
 
+        let mut track_refs = track_refs.into_iter();
+        let mut last_entry_id = None;
+        let mut ignored_track_refs = Vec::new();
+
         while let Some(next_track_ref) = track_refs.next() {
-            let Some(next_entry) = existing_entries.next() else {
+            let Some(next_entry_id) = existing_entries.peek().map(|entry| entry.id) else {
                 // All existing entries have been reused.
                 // The remaining tracks need to be added as new entries.
                 let remaining_ignored_tracks_refs = Self::append_tracks(
-                    executor,
+                    connection,
                     id,
                     std::iter::once(next_track_ref).chain(track_refs),
                 )
@@ -245,43 +596,38 @@ impl Playlist {
             };
 
             // Update entry.
-            if next_track_ref != next_entry.track_ref() {
-                let OriginTrackRef {
-                    id: track_id,
-                    db_uuid,
-                } = &next_track_ref;
-                debug_assert!(*track_id > TrackId::INVALID_ZERO);
-                let query_result = sqlx::query(
-                    r#"UPDATE OR IGNORE "PlaylistEntity"
-                    SET "trackId"=?1, "databaseUuid"=?2
-                    WHERE "id"=?3"#,
-                )
-                .bind(track_id)
-                .bind(db_uuid)
-                .bind(next_entry.id)
-                .execute(executor())
-                .await?;
+            let OriginTrackRef {
+                id: track_id,
+                db_uuid,
+            } = &next_track_ref;
+            debug_assert!(*track_id > TrackId::INVALID_ZERO);
+            let query_result = sqlx::query(
+                r#"UPDATE OR IGNORE "PlaylistEntity"
+                SET "trackId"=?1, "databaseUuid"=?2
+                WHERE "id"=?3"#,
+            )
+            .bind(track_id)
+            .bind(db_uuid)
+            .bind(next_entry_id)
+            .execute(&mut *connection)
+            .await?;
 
-                debug_assert!(query_result.rows_affected() <= 1);
-                if query_result.rows_affected() == 0 {
-                    // Ignore duplicate tracks.
-                    ignored_track_refs.push(next_track_ref);
-                    continue;
-                }
+            debug_assert!(query_result.rows_affected() <= 1);
+            if query_result.rows_affected() == 0 {
+                // Ignore duplicate tracks and reuse the entry for the next track.
+                ignored_track_refs.push(next_track_ref);
+                continue;
             }
 
             // Prepare next iteration.
-            last_entry_id = Some(next_entry.id);
+            last_entry_id = Some(next_entry_id);
+            existing_entries.next();
         }
 
         let Some(last_entry_id) = last_entry_id else {
             // Playlist is empty.
-            debug_assert_eq!(
-                PlaylistEntity::count_list(executor(), id).await.ok(),
-                Some(0)
-            );
-            let _query_result = PlaylistEntity::delete_list(executor(), id).await?;
-            return Ok(Vec::new());
+            let _query_result = PlaylistEntity::delete_list(&mut *connection, id).await?;
+            return Ok(ignored_track_refs);
         };
 
         // Terminate linked list.
@@ -292,16 +638,16 @@ impl Playlist {
         )
         .bind(PlaylistEntityId::INVALID_ZERO)
         .bind(last_entry_id)
-        .execute(executor())
+        .execute(&mut *connection)
         .await?;
 
         // Delete unused/obsolete entries.
         let _query_result = sqlx::query(
             r#"DELETE FROM "PlaylistEntity"
-            WHERE "trackId"<0"#,
+            WHERE "listId"=?1 AND "trackId"<0"#,
         )
         .bind(id)
-        .execute(executor())
+        .execute(&mut *connection)
         .await?;
 
         Ok(ignored_track_refs)
@@ -391,6 +737,85 @@ impl PlaylistEntity {
         .fetch(executor)
     }
 
+    /// Fetches all entries of a [`Playlist`] together with their [`Track`]s.
+    ///
+    /// Tracks of entries that refer to an external database are resolved
+    /// by their origin. Ordered by the canonical position in the playlist.
+    #[must_use]
+    pub fn fetch_list_with_tracks<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        list_id: PlaylistId,
+    ) -> BoxStream<'a, sqlx::Result<PlaylistEntityWithTrack>> {
+        sqlx::query_as(
+            r#"SELECT "PlaylistEntity"."id" AS "entityId","listId","trackId","databaseUuid","nextEntityId","membershipReference","Track".*
+               FROM "PlaylistEntity" LEFT JOIN "Track" ON "Track"."id"=(CASE
+                 WHEN "databaseUuid"=(SELECT "uuid" FROM "Information") THEN "trackId"
+                 ELSE (SELECT "id" FROM "Track"
+                       WHERE "originDatabaseUuid"="PlaylistEntity"."databaseUuid"
+                       AND "originTrackId"="PlaylistEntity"."trackId"
+                       ORDER BY "id" LIMIT 1)
+               END)
+               WHERE "listId"=?1 ORDER BY "membershipReference""#,
+        )
+        .bind(list_id)
+        .fetch(executor)
+    }
+
+    /// Fetches all entries of a [`Playlist`] by following the linked list.
+    ///
+    /// Starts with the entry that has no predecessor and follows the
+    /// `nextEntityId` pointers. The traversal is bounded by the number of
+    /// entries in the list to terminate even if the list contains cycles.
+    #[must_use]
+    pub fn fetch_list_ordered_by_chain(
+        pool: &SqlitePool,
+        list_id: PlaylistId,
+    ) -> BoxStream<'_, anyhow::Result<Self>> {
+        sqlx::query_as(
+            r#"WITH RECURSIVE "chain" AS (
+                 SELECT *, 0 AS "depth" FROM "PlaylistEntity"
+                 WHERE "listId"=?1 AND "id" NOT IN (
+                   SELECT "nextEntityId" FROM "PlaylistEntity"
+                   WHERE "listId"=?1 AND "nextEntityId"!=0
+                 )
+                 UNION ALL
+                 SELECT "pe".*, "c"."depth"+1 FROM "PlaylistEntity" "pe"
+                 JOIN "chain" "c" ON "pe"."id"="c"."nextEntityId"
+                 WHERE "pe"."listId"=?1
+                 AND "c"."depth"+1<(SELECT COUNT(*) FROM "PlaylistEntity" WHERE "listId"=?1)
+               )
+               SELECT * FROM "chain" ORDER BY "depth""#,
+        )
+        .bind(list_id)
+        .fetch(pool)
+        .map_err(Into::into)
+        .boxed()
+    }
+
+    /// Loads the tracks of all entries of a [`Playlist`] with their file paths.
+    ///
+    /// Tracks of entries that refer to an external database are resolved
+    /// by their origin, see [`Self::fetch_list_with_tracks()`].
+    ///
+    /// Ordered by the canonical position in the playlist. Entries that
+    /// reference missing tracks or tracks without a path are skipped.
+    pub(crate) async fn load_list_tracks_with_file_paths(
+        pool: &SqlitePool,
+        list_id: PlaylistId,
+        library_path: &LibraryPath,
+    ) -> sqlx::Result<Vec<(Track, FilePath<'static>)>> {
+        Self::fetch_list_with_tracks(pool, list_id)
+            .try_filter_map(|PlaylistEntityWithTrack { entity: _, track }| {
+                let track_with_file_path = track.and_then(|track| {
+                    let file_path = track.to_file_path(library_path)?;
+                    Some((track, file_path))
+                });
+                future::ready(Ok(track_with_file_path))
+            })
+            .try_collect()
+            .await
+    }
+
     /// Loads all entries of a [`Playlist`].
     ///
     /// Ordered by the canonical position in the playlist.
@@ -417,6 +842,91 @@ impl PlaylistEntity {
             .await
     }
 
+    /// Deletes a single entry and unlinks it from its [`Playlist`].
+    ///
+    /// The preceding entry is relinked to the following entry.
+    ///
+    /// Returns `false` if the entry does not exist.
+    pub async fn delete_and_unlink(
+        connection: &mut SqliteConnection,
+        id: PlaylistEntityId,
+    ) -> sqlx::Result<bool> {
+        sqlx::query(
+            r#"UPDATE "PlaylistEntity"
+               SET "nextEntityId"=(SELECT "nextEntityId" FROM "PlaylistEntity" WHERE "id"=?1)
+               WHERE "nextEntityId"=?1"#,
+        )
+        .bind(id)
+        .execute(&mut *connection)
+        .await?;
+        let result = sqlx::query(r#"DELETE FROM "PlaylistEntity" WHERE "id"=?1"#)
+            .bind(id)
+            .execute(connection)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Deletes all entries that reference tracks of the given database.
+    ///
+    /// The remaining entries of the affected playlists are relinked
+    /// in the order of their membership reference.
+    ///
+    /// Returns the number of deleted entries.
+    ///
+    /// Must run within a transaction in isolation.
+    pub async fn delete_all_with_uuid(
+        connection: &mut SqliteConnection,
+        db_uuid: &DbUuid,
+    ) -> sqlx::Result<u64> {
+        Self::delete_all_and_relink(connection, r#""databaseUuid"=?1"#, db_uuid).await
+    }
+
+    /// Deletes all entries that reference tracks of external databases.
+    ///
+    /// Like [`Self::delete_all_with_uuid()`], but deletes all entries
+    /// except those that reference tracks of the local database.
+    ///
+    /// Must run within a transaction in isolation.
+    pub async fn delete_all_external(
+        connection: &mut SqliteConnection,
+        local_db_uuid: &DbUuid,
+    ) -> sqlx::Result<u64> {
+        Self::delete_all_and_relink(connection, r#""databaseUuid"<>?1"#, local_db_uuid).await
+    }
+
+    /// Deletes all entries that match the condition and relinks the remaining entries.
+    ///
+    /// The condition must only reference the UUID as parameter `?1`.
+    async fn delete_all_and_relink(
+        connection: &mut SqliteConnection,
+        condition: &str,
+        db_uuid: &DbUuid,
+    ) -> sqlx::Result<u64> {
+        // Link each remaining entry with a deleted successor to the next remaining entry.
+        sqlx::query(&format!(
+            r#"UPDATE "PlaylistEntity" AS "entry"
+               SET "nextEntityId"=COALESCE((
+                   SELECT "next"."id" FROM "PlaylistEntity" AS "next"
+                   WHERE "next"."listId"="entry"."listId"
+                   AND "next"."membershipReference">"entry"."membershipReference"
+                   AND NOT ("next".{condition})
+                   ORDER BY "next"."membershipReference" LIMIT 1), ?2)
+               WHERE NOT ("entry".{condition})
+               AND "entry"."nextEntityId" IN (SELECT "id" FROM "PlaylistEntity" WHERE {condition})"#
+        ))
+        .bind(db_uuid)
+        .bind(PlaylistEntityId::INVALID_ZERO)
+        .execute(&mut *connection)
+        .await?;
+        let result = sqlx::query(&format!(
+            r#"DELETE FROM "PlaylistEntity" WHERE {condition}"#
+        ))
+        .bind(db_uuid)
+        .execute(connection)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     pub async fn count_list(
         executor: impl SqliteExecutor<'_>,
         list_id: PlaylistId,
@@ -430,33 +940,54 @@ impl PlaylistEntity {
         Ok(count.cast_unsigned())
     }
 
+    /// Counts the entries of all non-empty [`Playlist`]s.
+    ///
+    /// Ordered by playlist id.
+    pub async fn count_all_lists(
+        executor: impl SqliteExecutor<'_>,
+    ) -> sqlx::Result<Vec<(PlaylistId, u64)>> {
+        let counts = sqlx::query_as::<_, (PlaylistId, i64)>(
+            r#"SELECT "listId",COUNT(*) FROM "PlaylistEntity" GROUP BY "listId" ORDER BY "listId""#,
+        )
+        .fetch_all(executor)
+        .await?;
+        Ok(counts
+            .into_iter()
+            .map(|(list_id, count)| {
+                debug_assert!(count > 0);
+                (list_id, count.cast_unsigned())
+            })
+            .collect())
+    }
+
     /// Reads the (unambiguous) database UUID for this playlist's entries.
     ///
     /// Returns `Ok(None)` if the requested [`Playlist`] has no entries.
-    pub async fn try_load_db_uuid_of_list<'e, E>(
-        mut executor: impl FnMut() -> E,
+    pub async fn try_load_db_uuid_of_list(
+        connection: &mut SqliteConnection,
         list_id: PlaylistId,
-    ) -> anyhow::Result<Option<DbUuid>>
-    where
-        E: SqliteExecutor<'e>,
-    {
+    ) -> DatabaseResult<Option<DbUuid>> {
         let mut uuid_results = sqlx::query_scalar(
             r#"SELECT DISTINCT "databaseUuid" FROM "PlaylistEntity" WHERE "listId"=?1 LIMIT 2"#,
         )
         .bind(list_id)
-        .fetch(executor());
+        .fetch(&mut *connection);
 
         let Some(uuid_result) = uuid_results.next().await else {
             // Playlist is empty.
+            drop(uuid_results);
             debug_assert_eq!(
-                PlaylistEntity::count_list(executor(), list_id).await.ok(),
+                PlaylistEntity::count_list(&mut *connection, list_id)
+                    .await
+                    .ok(),
                 Some(0)
             );
             return Ok(None);
         };
         let uuid = uuid_result?;
         if uuid_results.next().await.is_some() {
-            bail!("playlist entries reference multiple database UUIDs");
+            // Playlist entries reference multiple database UUIDs.
+            return Err(DatabaseError::Ambiguous);
         }
         Ok(Some(uuid))
     }
@@ -465,13 +996,10 @@ impl PlaylistEntity {
     ///
     /// Returns `Ok(None)` if the list is empty. Fails if the last item
     /// is ambiguous.
-    pub async fn try_load_last_of_list<'e, E>(
-        mut executor: impl FnMut() -> E,
+    pub async fn try_load_last_of_list(
+        connection: &mut SqliteConnection,
         list_id: PlaylistId,
-    ) -> anyhow::Result<Option<Self>>
-    where
-        E: SqliteExecutor<'e>,
-    {
+    ) -> anyhow::Result<Option<Self>> {
         let mut last_entity_results = sqlx::query_as(
             r#"SELECT * FROM "PlaylistEntity"
                WHERE "listId"=?1
@@ -479,12 +1007,15 @@ impl PlaylistEntity {
                LIMIT 2"#,
         )
         .bind(list_id)
-        .fetch(executor());
+        .fetch(&mut *connection);
 
         let Some(last_entity_result) = last_entity_results.next().await else {
             // Playlist is empty.
+            drop(last_entity_results);
             debug_assert_eq!(
-                PlaylistEntity::count_list(executor(), list_id).await.ok(),
+                PlaylistEntity::count_list(&mut *connection, list_id)
+                    .await
+                    .ok(),
                 Some(0)
             );
             return Ok(None);
@@ -524,6 +1055,34 @@ impl PlaylistEntity {
     }
 }
 
+/// A [`PlaylistEntity`] together with its [`Track`].
+#[derive(Debug, Clone)]
+pub struct PlaylistEntityWithTrack {
+    pub entity: PlaylistEntity,
+    /// `None` if the referenced [`Track`] does not exist.
+    pub track: Option<Track>,
+}
+
+impl FromRow<'_, SqliteRow> for PlaylistEntityWithTrack {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        // The id of the entry is renamed to avoid a conflict with the id of the track.
+        let entity = PlaylistEntity {
+            id: row.try_get("entityId")?,
+            list_id: row.try_get("listId")?,
+            track_id: row.try_get("trackId")?,
+            database_uuid: row.try_get("databaseUuid")?,
+            next_entity_id: row.try_get("nextEntityId")?,
+            membership_reference: row.try_get("membershipReference")?,
+        };
+        let track = if row.try_get::<Option<TrackId>, _>("id")?.is_some() {
+            Some(Track::from_row(row)?)
+        } else {
+            None
+        };
+        Ok(Self { entity, track })
+    }
+}
+
 crate::db_id!(PlaylistAllChildrenId);
 
 #[derive(Debug, Clone, FromRow)]
@@ -552,6 +1111,61 @@ pub struct PlaylistPath {
     pub position: i64,
 }
 
+impl PlaylistPath {
+    /// Splits a path string into its segments.
+    ///
+    /// Inverse of [`concat_playlist_path_segments_to_string()`]. In contrast
+    /// to [`split_playlist_path_into_segments()`] this function never fails
+    /// and silently skips all empty segments, including the one after the
+    /// trailing separator.
+    #[must_use]
+    pub fn parse_segments(path: &str) -> Vec<&str> {
+        path.split(PLAYLIST_PATH_SEGMENT_SEPARATOR)
+            .filter(|segment| !segment.is_empty())
+            .collect()
+    }
+
+    /// Fetches all [`PlaylistPath`]s.
+    ///
+    /// Ordered by position, i.e. lexicographically by path.
+    #[must_use]
+    pub fn fetch_all<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "PlaylistPath" ORDER BY "position""#).fetch(executor)
+    }
+
+    /// Loads the [`PlaylistPath`] of a single [`Playlist`].
+    ///
+    /// Returns `Ok(None)` if the requested [`Playlist`] has not been found.
+    pub async fn try_load(
+        executor: impl SqliteExecutor<'_>,
+        id: PlaylistId,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "PlaylistPath" WHERE "id"=?1"#)
+            .bind(id)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// Fetches the [`PlaylistPath`]s of all playlists that contain a track.
+    ///
+    /// Ordered by position, i.e. lexicographically by path.
+    #[must_use]
+    pub fn fetch_all_containing_track<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        track_id: TrackId,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(
+            r#"SELECT "PlaylistPath".* FROM "PlaylistPath"
+               WHERE "id" IN (SELECT "listId" FROM "PlaylistEntity" WHERE "trackId"=?1)
+               ORDER BY "position""#,
+        )
+        .bind(track_id)
+        .fetch(executor)
+    }
+}
+
 pub const PLAYLIST_PATH_SEGMENT_SEPARATOR: &str = ";";
 
 #[must_use]
@@ -559,6 +1173,26 @@ pub fn is_valid_playlist_path_segment(segment: &str) -> bool {
     !segment.is_empty() && !segment.contains(PLAYLIST_PATH_SEGMENT_SEPARATOR)
 }
 
+/// Splits a playlist path into its segments.
+///
+/// A single trailing separator is ignored. Fails if the path is empty
+/// or contains empty segments.
+pub fn split_playlist_path_into_segments(path: &str) -> anyhow::Result<Vec<&str>> {
+    let path = path
+        .strip_suffix(PLAYLIST_PATH_SEGMENT_SEPARATOR)
+        .unwrap_or(path);
+    if path.is_empty() {
+        bail!("empty playlist path");
+    }
+    let segments = path
+        .split(PLAYLIST_PATH_SEGMENT_SEPARATOR)
+        .collect::<Vec<_>>();
+    if !segments.iter().copied().all(is_valid_playlist_path_segment) {
+        bail!("invalid playlist path \"{path}\"");
+    }
+    Ok(segments)
+}
+
 #[must_use]
 pub fn concat_playlist_path_segments_to_string<'s, S>(
     segments: impl IntoIterator<Item = &'s S>,
@@ -578,8 +1212,148 @@ where
         .collect()
 }
 
+/// Recursively builds the nodes of all children of a parent.
+///
+/// Consumes the entries in the map, i.e. playlists that are not reachable
+/// from the parent are left behind.
+fn build_playlist_nodes(
+    children_by_parent_id: &mut HashMap<PlaylistId, Vec<Playlist>>,
+    parent_id: PlaylistId,
+) -> Vec<PlaylistNode> {
+    let Some(children) = children_by_parent_id.remove(&parent_id) else {
+        return Vec::new();
+    };
+    order_sibling_playlists(children)
+        .into_iter()
+        .map(|playlist| {
+            let children = build_playlist_nodes(children_by_parent_id, playlist.id);
+            PlaylistNode { playlist, children }
+        })
+        .collect()
+}
+
+/// Orders siblings by following the `nextListId` chain.
+///
+/// If the chain is broken into multiple parts then these parts are
+/// concatenated in the original order of their heads. Siblings that are
+/// not reachable from any head, i.e. cycles, are appended in their
+/// original order.
+fn order_sibling_playlists(siblings: Vec<Playlist>) -> Vec<Playlist> {
+    let mut siblings_by_id = siblings
+        .iter()
+        .map(|playlist| (playlist.id, playlist.clone()))
+        .collect::<HashMap<_, _>>();
+    let head_ids = siblings
+        .iter()
+        .filter(|playlist| {
+            !siblings
+                .iter()
+                .any(|sibling| sibling.next_list_id == playlist.id)
+        })
+        .map(|playlist| playlist.id)
+        .collect::<Vec<_>>();
+    let mut ordered = Vec::with_capacity(siblings.len());
+    for head_id in head_ids {
+        let mut next_id = head_id;
+        while let Some(playlist) = siblings_by_id.remove(&next_id) {
+            next_id = playlist.next_list_id;
+            ordered.push(playlist);
+        }
+    }
+    ordered.extend(
+        siblings
+            .into_iter()
+            .filter(|playlist| siblings_by_id.contains_key(&playlist.id)),
+    );
+    ordered
+}
+
 #[cfg(test)]
 mod tests {
+    use sqlx::types::time::PrimitiveDateTime;
+
+    use super::{Playlist, PlaylistId, PlaylistPath};
+
+    fn new_playlist(id: i64, next_list_id: i64) -> Playlist {
+        Playlist {
+            id: PlaylistId::new(id),
+            title: id.to_string(),
+            parent_list_id: PlaylistId::INVALID_ZERO,
+            is_persisted: true,
+            next_list_id: PlaylistId::new(next_list_id),
+            last_edit_time: PrimitiveDateTime::MIN,
+            is_explicitly_exported: true,
+        }
+    }
+
+    fn playlist_ids(playlists: &[Playlist]) -> Vec<i64> {
+        playlists
+            .iter()
+            .map(|playlist| playlist.id.to_raw())
+            .collect()
+    }
+
+    #[test]
+    fn order_sibling_playlists() {
+        let siblings = vec![new_playlist(1, 0), new_playlist(2, 3), new_playlist(3, 1)];
+        assert_eq!(
+            playlist_ids(&super::order_sibling_playlists(siblings)),
+            [2, 3, 1]
+        );
+        // Cycle without a head.
+        let siblings = vec![new_playlist(1, 2), new_playlist(2, 1)];
+        assert_eq!(
+            playlist_ids(&super::order_sibling_playlists(siblings)),
+            [1, 2]
+        );
+        // Broken chain.
+        let siblings = vec![new_playlist(1, 0), new_playlist(2, 0), new_playlist(3, 2)];
+        assert_eq!(
+            playlist_ids(&super::order_sibling_playlists(siblings)),
+            [1, 3, 2]
+        );
+    }
+
+    #[test]
+    fn parse_playlist_path_segments() {
+        assert!(PlaylistPath::parse_segments("").is_empty());
+        assert!(PlaylistPath::parse_segments(";").is_empty());
+        assert_eq!(PlaylistPath::parse_segments("foo"), ["foo"]);
+        assert_eq!(PlaylistPath::parse_segments("foo;"), ["foo"]);
+        assert_eq!(
+            PlaylistPath::parse_segments("foo bar;baz;"),
+            ["foo bar", "baz"]
+        );
+        assert_eq!(PlaylistPath::parse_segments("foo;;bar"), ["foo", "bar"]);
+        let segments = ["foo", "bar", "baz"];
+        assert_eq!(
+            PlaylistPath::parse_segments(&super::concat_playlist_path_segments_to_string(
+                &segments
+            )),
+            segments
+        );
+    }
+
+    #[test]
+    fn split_playlist_path_into_segments() {
+        assert_eq!(
+            super::split_playlist_path_into_segments("foo").unwrap(),
+            ["foo"]
+        );
+        assert_eq!(
+            super::split_playlist_path_into_segments("foo;bar;").unwrap(),
+            ["foo", "bar"]
+        );
+        assert_eq!(
+            super::split_playlist_path_into_segments("foo bar;baz").unwrap(),
+            ["foo bar", "baz"]
+        );
+        assert!(super::split_playlist_path_into_segments("").is_err());
+        assert!(super::split_playlist_path_into_segments(";").is_err());
+        assert!(super::split_playlist_path_into_segments("foo;;bar").is_err());
+        assert!(super::split_playlist_path_into_segments("foo;;").is_err());
+    }
+
     #[test]
     fn concat_playlist_path_segments_to_string() {
         assert_eq!(
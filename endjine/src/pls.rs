@@ -0,0 +1,193 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+use anyhow::{Context as _, bail};
+use sqlx::SqlitePool;
+
+use crate::{
+    FilePath, LibraryPath, PlaylistEntity, PlaylistId,
+    file_uri::{file_path_from_uri, is_uri},
+};
+
+const PLS_HEADER: &str = "[playlist]";
+
+const PLS_VERSION: u32 = 2;
+
+/// Imports the file paths from a PLS playlist file.
+///
+/// Relative file paths are resolved against `base_path` that defaults
+/// to the parent directory of the PLS file.
+pub fn import_pls_file(
+    path: &Path,
+    base_path: Option<&Path>,
+) -> anyhow::Result<Vec<FilePath<'static>>> {
+    let file = File::open(path).context("open PLS file")?;
+    let base_path = base_path.or_else(|| path.parent());
+    read_pls(BufReader::new(file), base_path)
+}
+
+/// Reads the file paths from a PLS playlist.
+///
+/// Entries are ordered by their number, i.e. `File1` comes before `File2`.
+/// Only the `FileN` entries are evaluated, the optional `TitleN` and
+/// `LengthN` entries are ignored.
+///
+/// Relative file paths are resolved against `base_path`. Fails if no
+/// `base_path` is given and the playlist contains relative file paths.
+pub fn read_pls(
+    reader: impl BufRead,
+    base_path: Option<&Path>,
+) -> anyhow::Result<Vec<FilePath<'static>>> {
+    let mut has_header = false;
+    let mut files = BTreeMap::new();
+    for (line_index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with([';', '#']) {
+            continue;
+        }
+        let line_number = line_index + 1;
+        if !has_header {
+            if !line.eq_ignore_ascii_case(PLS_HEADER) {
+                bail!("missing {PLS_HEADER} header in line {line_number}");
+            }
+            has_header = true;
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("invalid entry in line {line_number}");
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let Some(number) = strip_prefix_ignore_ascii_case(key, "File") else {
+            // Ignore TitleN, LengthN, NumberOfEntries, Version, and all other keys.
+            continue;
+        };
+        let number = number
+            .parse::<u32>()
+            .with_context(|| format!("invalid key \"{key}\" in line {line_number}"))?;
+        let file_path = import_pls_entry(value, base_path)
+            .with_context(|| format!("invalid file path in line {line_number}"))?;
+        if files.insert(number, file_path).is_some() {
+            bail!("duplicate key \"{key}\" in line {line_number}");
+        }
+    }
+    if !has_header {
+        bail!("missing {PLS_HEADER} header");
+    }
+    Ok(files.into_values().collect())
+}
+
+fn strip_prefix_ignore_ascii_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &s[prefix.len()..])
+}
+
+fn import_pls_entry(entry: &str, base_path: Option<&Path>) -> anyhow::Result<FilePath<'static>> {
+    if entry.is_empty() {
+        bail!("empty file path");
+    }
+    let file_path = if is_uri(entry) {
+        file_path_from_uri(entry)?
+    } else {
+        Path::new(entry).to_path_buf()
+    };
+    if file_path.is_absolute() {
+        return Ok(FilePath::import_path(&file_path));
+    }
+    let Some(base_path) = base_path else {
+        bail!("unresolved relative file path \"{entry}\"");
+    };
+    Ok(FilePath::import_path(&base_path.join(file_path)))
+}
+
+/// Exports the tracks of a playlist as PLS.
+///
+/// Entries that reference missing tracks or tracks without a path are
+/// skipped.
+///
+/// Returns the number of entries written.
+pub async fn export_playlist_to_pls(
+    pool: &SqlitePool,
+    playlist_id: PlaylistId,
+    library_path: &LibraryPath,
+    mut output: impl Write,
+) -> anyhow::Result<u64> {
+    let tracks =
+        PlaylistEntity::load_list_tracks_with_file_paths(pool, playlist_id, library_path).await?;
+    writeln!(output, "{PLS_HEADER}")?;
+    let mut entry_count = 0;
+    for (track, file_path) in tracks {
+        entry_count += 1;
+        writeln!(output, "File{entry_count}={file_path}")?;
+        if let Some(display_name) = track.display_name() {
+            // Line breaks would corrupt the file.
+            let display_name = display_name.replace(['\r', '\n'], " ");
+            writeln!(output, "Title{entry_count}={display_name}")?;
+        }
+        // -1 denotes an unknown length.
+        let length = track.length.map_or(-1, u64::cast_signed);
+        writeln!(output, "Length{entry_count}={length}")?;
+    }
+    writeln!(output, "NumberOfEntries={entry_count}")?;
+    writeln!(output, "Version={PLS_VERSION}")?;
+    output.flush()?;
+    Ok(entry_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::FilePath;
+
+    use super::read_pls;
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn read_entries_ordered_by_number() {
+        let pls = "\
+[playlist]
+; Comment
+File2=b.mp3
+Title2=B
+File1=/music/a.mp3
+Length1=185
+file3=file:///music/c%20d.mp3
+NumberOfEntries=3
+Version=2
+";
+        let file_paths = read_pls(pls.as_bytes(), Some(Path::new("/base"))).unwrap();
+        assert_eq!(
+            file_paths,
+            [
+                FilePath::import_path("/music/a.mp3"),
+                FilePath::import_path("/base/b.mp3"),
+                FilePath::import_path("/music/c d.mp3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_invalid() {
+        assert!(read_pls("".as_bytes(), None).is_err());
+        assert!(read_pls("File1=/a.mp3\n".as_bytes(), None).is_err());
+        assert!(read_pls("[playlist]\nFileX=/a.mp3\n".as_bytes(), None).is_err());
+        assert!(read_pls("[playlist]\nFile1=/a.mp3\nFile1=/b.mp3\n".as_bytes(), None).is_err());
+        assert!(read_pls("[playlist]\nFile1=a.mp3\n".as_bytes(), None).is_err());
+        assert!(
+            read_pls(
+                "[playlist]\nFile1=http://example.com/a.mp3\n".as_bytes(),
+                None
+            )
+            .is_err()
+        );
+    }
+}
@@ -41,6 +41,29 @@ impl PreparelistEntity {
         sqlx::query_as(r#"SELECT * FROM "PreparelistEntity" ORDER BY "id""#).fetch(executor)
     }
 
+    /// Counts all [`PreparelistEntity`]s.
+    pub async fn count(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
+        let count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "PreparelistEntity""#)
+            .fetch_one(executor)
+            .await?;
+        debug_assert!(count >= 0);
+        Ok(count.cast_unsigned())
+    }
+
+    /// Checks if a track is contained in the _Preparelist_.
+    pub async fn contains_track(
+        executor: impl SqliteExecutor<'_>,
+        track_id: TrackId,
+    ) -> sqlx::Result<bool> {
+        let (exists,) = sqlx::query_as(
+            r#"SELECT EXISTS(SELECT 1 FROM "PreparelistEntity" WHERE "trackId"=?1)"#,
+        )
+        .bind(track_id)
+        .fetch_one(executor)
+        .await?;
+        Ok(exists)
+    }
+
     /// Loads a single [`PreparelistEntity`] by ID.
     ///
     /// Returns `Ok(None)` if the requested [`PreparelistEntity`] has not been found.
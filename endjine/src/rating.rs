@@ -0,0 +1,101 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use sqlx::{
+    Decode, Encode, Sqlite,
+    encode::IsNull,
+    error::BoxDynError,
+    sqlite::{SqliteArgumentValue, SqliteTypeInfo, SqliteValueRef},
+};
+
+/// Rating of a track with 0 to 5 stars.
+///
+/// Engine DJ stores the rating as a percentage in steps of 20 per star
+/// in the `rating` column of `Track`, i.e. 3 stars are stored as 60.
+/// Values in between are rounded to the nearest number of stars when
+/// decoding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Rating(u8);
+
+impl Rating {
+    pub const MAX_STARS: u8 = 5;
+
+    const PERCENT_PER_STAR: i64 = 20;
+
+    /// Creates a rating from a number of stars.
+    ///
+    /// Returns `None` if the number of stars is out of range.
+    #[must_use]
+    pub fn try_new(stars: i64) -> Option<Self> {
+        u8::try_from(stars)
+            .ok()
+            .filter(|stars| *stars <= Self::MAX_STARS)
+            .map(Self)
+    }
+
+    #[must_use]
+    pub const fn as_stars(&self) -> u8 {
+        self.0
+    }
+
+    const fn to_percent(self) -> i64 {
+        self.0 as i64 * Self::PERCENT_PER_STAR
+    }
+
+    fn try_from_percent(percent: i64) -> Option<Self> {
+        if !(0..=100).contains(&percent) {
+            return None;
+        }
+        Self::try_new((percent + Self::PERCENT_PER_STAR / 2) / Self::PERCENT_PER_STAR)
+    }
+}
+
+impl sqlx::Type<Sqlite> for Rating {
+    fn type_info() -> SqliteTypeInfo {
+        <i64 as sqlx::Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <i64 as sqlx::Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for Rating {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let percent = <i64 as Decode<'r, Sqlite>>::decode(value)?;
+        Self::try_from_percent(percent).ok_or_else(|| format!("invalid rating {percent}").into())
+    }
+}
+
+impl<'q> Encode<'q, Sqlite> for Rating {
+    fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> Result<IsNull, BoxDynError> {
+        <i64 as Encode<Sqlite>>::encode_by_ref(&self.to_percent(), buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rating;
+
+    #[test]
+    fn try_new() {
+        assert_eq!(Some(0), Rating::try_new(0).map(|rating| rating.as_stars()));
+        assert_eq!(Some(5), Rating::try_new(5).map(|rating| rating.as_stars()));
+        assert_eq!(None, Rating::try_new(6));
+        assert_eq!(None, Rating::try_new(-1));
+    }
+
+    #[test]
+    fn percent_roundtrip() {
+        for stars in 0..=i64::from(Rating::MAX_STARS) {
+            let rating = Rating::try_new(stars).unwrap();
+            assert_eq!(stars * 20, rating.to_percent());
+            assert_eq!(Some(rating), Rating::try_from_percent(rating.to_percent()));
+        }
+        assert_eq!(Rating::try_new(3), Rating::try_from_percent(50));
+        assert_eq!(Rating::try_new(2), Rating::try_from_percent(49));
+        assert_eq!(None, Rating::try_from_percent(101));
+        assert_eq!(None, Rating::try_from_percent(-1));
+    }
+}
@@ -1,9 +1,21 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
+use std::fmt;
+
+use anyhow::bail;
 use futures_util::stream::BoxStream;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqliteExecutor, types::time::PrimitiveDateTime};
+use sqlx::{
+    FromRow, QueryBuilder, Sqlite, SqliteExecutor, SqlitePool,
+    types::{Json, time::PrimitiveDateTime},
+};
+
+use crate::{
+    DbUuid, ImportPlaylistMode, PLAYLIST_PATH_SEGMENT_SEPARATOR, Playlist, PlaylistId, Track,
+    TrackId, concat_playlist_path_segments_to_string, is_valid_playlist_path_segment,
+    utc_now_primitive_date_time,
+};
 
 crate::db_uuid!(SmartlistUuid);
 
@@ -54,6 +66,159 @@ impl Smartlist {
             .fetch_optional(executor)
             .await
     }
+
+    /// Creates a new [`Smartlist`].
+    ///
+    /// The `parent_path` is the terminated path of the parent playlist.
+    /// An empty `parent_path` denotes the root.
+    ///
+    /// Returns the UUID of the new [`Smartlist`].
+    pub async fn create(
+        executor: impl SqliteExecutor<'_>,
+        title: &str,
+        rules: &SmartlistRules,
+        parent_path: &str,
+    ) -> sqlx::Result<SmartlistUuid> {
+        if !is_valid_playlist_path_segment(title) {
+            return Err(sqlx::Error::InvalidArgument(format!(
+                "invalid smartlist title \"{title}\""
+            )));
+        }
+        if !parent_path.is_empty() && !parent_path.ends_with(PLAYLIST_PATH_SEGMENT_SEPARATOR) {
+            return Err(sqlx::Error::InvalidArgument(format!(
+                "unterminated parent playlist path \"{parent_path}\""
+            )));
+        }
+        let list_uuid = SmartlistUuid::new_random();
+        let last_edit_time = utc_now_primitive_date_time();
+        sqlx::query(
+            r#"INSERT INTO "Smartlist"
+               ("listUuid", "title", "parentPlaylistPath", "nextPlaylistPath", "nextListUuid", "rules", "lastEditTime")
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+        )
+        .bind(list_uuid)
+        .bind(title)
+        .bind(parent_path)
+        .bind("")
+        .bind(SmartlistUuid::nil())
+        .bind(Json(rules))
+        .bind(last_edit_time)
+        .execute(executor)
+        .await?;
+        Ok(list_uuid)
+    }
+
+    /// Deletes a [`Smartlist`] from the database.
+    ///
+    /// Returns `true` if the [`Smartlist`] has been deleted and `false`
+    /// if it has not been found.
+    ///
+    /// Does not cascade into [`Playlist`](crate::Playlist). The caller must
+    /// separately delete any materialized playlist of the same name.
+    pub async fn delete(
+        executor: impl SqliteExecutor<'_>,
+        uuid: &SmartlistUuid,
+    ) -> sqlx::Result<bool> {
+        sqlx::query(r#"DELETE FROM "Smartlist" WHERE "listUuid"=?1"#)
+            .bind(uuid)
+            .execute(executor)
+            .await
+            .map(|result| {
+                debug_assert!(result.rows_affected() <= 1);
+                result.rows_affected() > 0
+            })
+    }
+
+    /// Evaluates the rules of a [`Smartlist`].
+    ///
+    /// Returns the IDs of all matching tracks, ordered by ID.
+    pub async fn evaluate(pool: &SqlitePool, uuid: &SmartlistUuid) -> anyhow::Result<Vec<TrackId>> {
+        let Some(smartlist) = Self::try_load(pool, uuid).await? else {
+            bail!("smartlist {uuid} not found");
+        };
+        let mut query_builder = smartlist.rules.track_id_query_builder()?;
+        let track_ids = query_builder.build_query_scalar().fetch_all(pool).await?;
+        Ok(track_ids)
+    }
+
+    /// Exports a [`Smartlist`] as a regular [`Playlist`].
+    ///
+    /// Evaluates the rules and populates the [`Playlist`] with the same title
+    /// and parent as the [`Smartlist`]. The [`Playlist`] is created if it does
+    /// not exist yet.
+    ///
+    /// Runs within a transaction.
+    ///
+    /// Returns the ID of the [`Playlist`].
+    pub async fn export_to_playlist(
+        pool: &SqlitePool,
+        uuid: &SmartlistUuid,
+        local_db_uuid: DbUuid,
+        mode: ImportPlaylistMode,
+    ) -> anyhow::Result<PlaylistId> {
+        let mut tx = pool.begin().await?;
+
+        let Some(smartlist) = Self::try_load(&mut *tx, uuid).await? else {
+            bail!("smartlist {uuid} not found");
+        };
+        let mut query_builder = smartlist.rules.track_id_query_builder()?;
+        let track_ids: Vec<TrackId> = query_builder
+            .build_query_scalar()
+            .fetch_all(&mut *tx)
+            .await?;
+        let tracks = Track::find_many_by_id(&mut tx, &track_ids).await?;
+        let mut track_refs = Vec::with_capacity(track_ids.len());
+        for (track_id, track) in track_ids.into_iter().zip(tracks) {
+            let Some(track) = track else {
+                bail!("track {track_id} not found");
+            };
+            track_refs.push(track.to_ref().to_origin(local_db_uuid)?);
+        }
+
+        let parent_path = smartlist.parent_playlist_path.as_str();
+        let is_root = parent_path
+            .trim_end_matches(PLAYLIST_PATH_SEGMENT_SEPARATOR)
+            .is_empty();
+        let path = if is_root {
+            concat_playlist_path_segments_to_string([smartlist.title.as_str()])
+        } else {
+            debug_assert!(parent_path.ends_with(PLAYLIST_PATH_SEGMENT_SEPARATOR));
+            [
+                parent_path,
+                &smartlist.title,
+                PLAYLIST_PATH_SEGMENT_SEPARATOR,
+            ]
+            .concat()
+        };
+        let playlist_id = if let Some(playlist_id) =
+            Playlist::find_id_by_path(&mut *tx, &path).await?
+        {
+            playlist_id
+        } else {
+            let parent_list_id = if is_root {
+                PlaylistId::INVALID_ZERO
+            } else {
+                let Some(parent_list_id) = Playlist::find_id_by_path(&mut *tx, parent_path).await?
+                else {
+                    bail!("parent playlist \"{parent_path}\" not found");
+                };
+                parent_list_id
+            };
+            Playlist::create(&mut tx, parent_list_id, &smartlist.title, true).await?
+        };
+
+        match mode {
+            ImportPlaylistMode::Append => {
+                Playlist::append_tracks(&mut tx, playlist_id, track_refs).await?;
+            }
+            ImportPlaylistMode::Replace => {
+                Playlist::replace_tracks(&mut tx, playlist_id, track_refs).await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(playlist_id)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -64,6 +229,43 @@ pub struct SmartlistRules {
     pub rv: u32,
 }
 
+impl SmartlistRules {
+    /// Builds a query that selects the IDs of all matching tracks.
+    fn track_id_query_builder(&self) -> anyhow::Result<QueryBuilder<'static, Sqlite>> {
+        let Self {
+            r#match,
+            rules,
+            rv: _,
+        } = self;
+        let mut query_builder = QueryBuilder::new(r#"SELECT "id" FROM "Track" WHERE "#);
+        if rules.is_empty() {
+            // Neutral element of the logical operator.
+            query_builder.push(match r#match {
+                SmartlistRulesMatch::One => "FALSE",
+                SmartlistRulesMatch::All => "TRUE",
+            });
+        } else {
+            let separator = match r#match {
+                SmartlistRulesMatch::One => " OR ",
+                SmartlistRulesMatch::All => " AND ",
+            };
+            for (index, item) in rules.iter().enumerate() {
+                if index > 0 {
+                    query_builder.push(separator);
+                }
+                item.push_condition(&mut query_builder)?;
+            }
+        }
+        query_builder.push(r#" ORDER BY "id""#);
+        Ok(query_builder)
+    }
+}
+
+/// A single rule of a [`Smartlist`].
+///
+/// The column `col` is compared with the operand `param` according
+/// to the condition `con`. The condition `between` uses `v` as the
+/// (inclusive) upper bound.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SmartlistRulesItem {
     pub col: String,
@@ -72,9 +274,350 @@ pub struct SmartlistRulesItem {
     pub v: String,
 }
 
+impl SmartlistRulesItem {
+    /// Parses the column and the condition.
+    pub fn typed(&self) -> anyhow::Result<(SmartlistColumn, SmartlistCondition)> {
+        let Self { col, con, .. } = self;
+        let column = SmartlistColumn::try_from(col.as_str())?;
+        let condition = SmartlistCondition::try_from(con.as_str())?;
+        Ok((column, condition))
+    }
+
+    /// Appends the condition as a parenthesized SQL expression.
+    fn push_condition(
+        &self,
+        query_builder: &mut QueryBuilder<'static, Sqlite>,
+    ) -> anyhow::Result<()> {
+        let (column, condition) = self.typed()?;
+        let Self { param, v, .. } = self;
+        query_builder.push("(").push(column.sql_column());
+        match (condition, column.is_numeric()) {
+            (SmartlistCondition::Contains, false) => {
+                query_builder
+                    .push(r" LIKE ")
+                    .push_bind(format!("%{}%", escape_like_pattern(param)))
+                    .push(r" ESCAPE '\'");
+            }
+            (SmartlistCondition::StartsWith, false) => {
+                query_builder
+                    .push(r" LIKE ")
+                    .push_bind(format!("{}%", escape_like_pattern(param)))
+                    .push(r" ESCAPE '\'");
+            }
+            (SmartlistCondition::Is, false) => {
+                query_builder
+                    .push(" IS ")
+                    .push_bind(param.clone())
+                    .push(" COLLATE NOCASE");
+            }
+            (SmartlistCondition::IsNot, false) => {
+                query_builder
+                    .push(" IS NOT ")
+                    .push_bind(param.clone())
+                    .push(" COLLATE NOCASE");
+            }
+            (SmartlistCondition::Is, true) => {
+                query_builder.push(" = ").push_bind(parse_numeric(param)?);
+            }
+            (SmartlistCondition::IsNot, true) => {
+                query_builder.push(" <> ").push_bind(parse_numeric(param)?);
+            }
+            (SmartlistCondition::GreaterThan, true) => {
+                query_builder.push(" > ").push_bind(parse_numeric(param)?);
+            }
+            (SmartlistCondition::GreaterThanOrEqual, true) => {
+                query_builder.push(" >= ").push_bind(parse_numeric(param)?);
+            }
+            (SmartlistCondition::LessThan, true) => {
+                query_builder.push(" < ").push_bind(parse_numeric(param)?);
+            }
+            (SmartlistCondition::LessThanOrEqual, true) => {
+                query_builder.push(" <= ").push_bind(parse_numeric(param)?);
+            }
+            (SmartlistCondition::Between, true) => {
+                query_builder
+                    .push(" BETWEEN ")
+                    .push_bind(parse_numeric(param)?)
+                    .push(" AND ")
+                    .push_bind(parse_numeric(v)?);
+            }
+            _ => bail!("unsupported smartlist condition \"{condition}\" for column \"{column}\""),
+        }
+        query_builder.push(")");
+        Ok(())
+    }
+}
+
+/// Column of a [`SmartlistRulesItem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SmartlistColumn {
+    Title,
+    Artist,
+    Album,
+    Genre,
+    Bpm,
+    Key,
+    Rating,
+    Year,
+    Comment,
+}
+
+impl SmartlistColumn {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Artist => "artist",
+            Self::Album => "album",
+            Self::Genre => "genre",
+            Self::Bpm => "bpm",
+            Self::Key => "key",
+            Self::Rating => "rating",
+            Self::Year => "year",
+            Self::Comment => "comment",
+        }
+    }
+
+    /// Checks if the column contains numeric values.
+    #[must_use]
+    pub const fn is_numeric(self) -> bool {
+        match self {
+            Self::Title | Self::Artist | Self::Album | Self::Genre | Self::Comment => false,
+            Self::Bpm | Self::Key | Self::Rating | Self::Year => true,
+        }
+    }
+
+    /// The quoted column name in the `Track` table.
+    #[must_use]
+    const fn sql_column(self) -> &'static str {
+        match self {
+            Self::Title => r#""title""#,
+            Self::Artist => r#""artist""#,
+            Self::Album => r#""album""#,
+            Self::Genre => r#""genre""#,
+            Self::Bpm => r#""bpmAnalyzed""#,
+            Self::Key => r#""key""#,
+            Self::Rating => r#""rating""#,
+            Self::Year => r#""year""#,
+            Self::Comment => r#""comment""#,
+        }
+    }
+}
+
+impl TryFrom<&str> for SmartlistColumn {
+    type Error = anyhow::Error;
+
+    fn try_from(from: &str) -> Result<Self, Self::Error> {
+        let column = match from {
+            "title" => Self::Title,
+            "artist" => Self::Artist,
+            "album" => Self::Album,
+            "genre" => Self::Genre,
+            "bpm" => Self::Bpm,
+            "key" => Self::Key,
+            "rating" => Self::Rating,
+            "year" => Self::Year,
+            "comment" => Self::Comment,
+            _ => bail!("unsupported smartlist column \"{from}\""),
+        };
+        Ok(column)
+    }
+}
+
+impl fmt::Display for SmartlistColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Condition of a [`SmartlistRulesItem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SmartlistCondition {
+    Contains,
+    Is,
+    IsNot,
+    StartsWith,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+    Between,
+}
+
+impl SmartlistCondition {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Contains => "contains",
+            Self::Is => "is",
+            Self::IsNot => "isNot",
+            Self::StartsWith => "startsWith",
+            Self::GreaterThan => "greaterThan",
+            Self::GreaterThanOrEqual => "greaterThanOrEqual",
+            Self::LessThan => "lessThan",
+            Self::LessThanOrEqual => "lessThanOrEqual",
+            Self::Between => "between",
+        }
+    }
+}
+
+impl TryFrom<&str> for SmartlistCondition {
+    type Error = anyhow::Error;
+
+    fn try_from(from: &str) -> Result<Self, Self::Error> {
+        let condition = match from {
+            "contains" => Self::Contains,
+            "is" => Self::Is,
+            "isNot" => Self::IsNot,
+            "startsWith" => Self::StartsWith,
+            "greaterThan" => Self::GreaterThan,
+            "greaterThanOrEqual" => Self::GreaterThanOrEqual,
+            "lessThan" => Self::LessThan,
+            "lessThanOrEqual" => Self::LessThanOrEqual,
+            "between" => Self::Between,
+            _ => bail!("unsupported smartlist condition \"{from}\""),
+        };
+        Ok(condition)
+    }
+}
+
+impl fmt::Display for SmartlistCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn parse_numeric(operand: &str) -> anyhow::Result<f64> {
+    operand
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid numeric operand \"{operand}\""))
+}
+
+/// Escapes the wildcard characters of a `LIKE` pattern.
+fn escape_like_pattern(pattern: &str) -> String {
+    let mut escaped = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum SmartlistRulesMatch {
     One,
     All,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SmartlistColumn, SmartlistCondition, SmartlistRules, SmartlistRulesItem,
+        SmartlistRulesMatch,
+    };
+
+    fn rules_item(col: &str, con: &str, param: &str, v: &str) -> SmartlistRulesItem {
+        SmartlistRulesItem {
+            col: col.to_owned(),
+            con: con.to_owned(),
+            param: param.to_owned(),
+            v: v.to_owned(),
+        }
+    }
+
+    #[test]
+    fn track_id_query() {
+        let rules = SmartlistRules {
+            r#match: SmartlistRulesMatch::All,
+            rules: vec![
+                rules_item("title", "contains", "50%", ""),
+                rules_item("bpm", "between", "120", "130"),
+            ],
+            rv: 1,
+        };
+        assert_eq!(
+            rules.track_id_query_builder().unwrap().sql(),
+            r#"SELECT "id" FROM "Track" WHERE ("title" LIKE ? ESCAPE '\') AND ("bpmAnalyzed" BETWEEN ? AND ?) ORDER BY "id""#
+        );
+
+        let rules = SmartlistRules {
+            r#match: SmartlistRulesMatch::One,
+            rules: vec![],
+            rv: 1,
+        };
+        assert_eq!(
+            rules.track_id_query_builder().unwrap().sql(),
+            r#"SELECT "id" FROM "Track" WHERE FALSE ORDER BY "id""#
+        );
+    }
+
+    #[test]
+    fn track_id_query_unsupported() {
+        for item in [
+            rules_item("unknown", "is", "foo", ""),
+            rules_item("title", "greaterThan", "foo", ""),
+            rules_item("rating", "contains", "80", ""),
+            rules_item("rating", "greaterThanOrEqual", "high", ""),
+        ] {
+            let rules = SmartlistRules {
+                r#match: SmartlistRulesMatch::All,
+                rules: vec![item],
+                rv: 1,
+            };
+            assert!(rules.track_id_query_builder().is_err());
+        }
+    }
+
+    #[test]
+    fn typed_rules_item() {
+        assert_eq!(
+            rules_item("bpm", "between", "120", "130").typed().unwrap(),
+            (SmartlistColumn::Bpm, SmartlistCondition::Between)
+        );
+        assert!(rules_item("bpm", "unknown", "", "").typed().is_err());
+        assert!(rules_item("unknown", "between", "", "").typed().is_err());
+    }
+
+    #[test]
+    fn column_and_condition_str_round_trip() {
+        for column in [
+            SmartlistColumn::Title,
+            SmartlistColumn::Artist,
+            SmartlistColumn::Album,
+            SmartlistColumn::Genre,
+            SmartlistColumn::Bpm,
+            SmartlistColumn::Key,
+            SmartlistColumn::Rating,
+            SmartlistColumn::Year,
+            SmartlistColumn::Comment,
+        ] {
+            assert_eq!(SmartlistColumn::try_from(column.as_str()).unwrap(), column);
+        }
+        for condition in [
+            SmartlistCondition::Contains,
+            SmartlistCondition::Is,
+            SmartlistCondition::IsNot,
+            SmartlistCondition::StartsWith,
+            SmartlistCondition::GreaterThan,
+            SmartlistCondition::GreaterThanOrEqual,
+            SmartlistCondition::LessThan,
+            SmartlistCondition::LessThanOrEqual,
+            SmartlistCondition::Between,
+        ] {
+            assert_eq!(
+                SmartlistCondition::try_from(condition.as_str()).unwrap(),
+                condition
+            );
+        }
+    }
+
+    #[test]
+    fn escape_like_pattern() {
+        assert_eq!(super::escape_like_pattern("foo"), "foo");
+        assert_eq!(super::escape_like_pattern(r"50%_\"), r"50\%\_\\");
+    }
+}
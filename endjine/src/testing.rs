@@ -0,0 +1,88 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! Utilities for testing.
+//!
+//! The databases created by these utilities only approximate the schema
+//! of Engine DJ, see `schema.sql`. They are suitable for testing this crate
+//! but must not be opened by Engine DJ.
+
+use std::{path::Path, str::FromStr as _};
+
+use anyhow::bail;
+use sqlx::{
+    SqlitePool,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+
+use crate::{DbUuid, Information, SchemaVersion};
+
+/// DDL for creating a new, empty database.
+const SCHEMA_SQL: &str = include_str!("testing/schema.sql");
+
+/// Creates a new, empty database.
+///
+/// Creates the database file with all known tables and inserts the
+/// [`Information`] record with the latest schema version as well as the
+/// NULL album art that is referenced by default.
+///
+/// Use [`DbUuid::new_random()`] for a fresh, unique `uuid`.
+///
+/// Fails if the file already exists.
+pub async fn create_database(
+    file_path: impl AsRef<Path>,
+    uuid: DbUuid,
+) -> anyhow::Result<(SqlitePool, Information)> {
+    let file_path = file_path.as_ref();
+    if file_path.try_exists()? {
+        bail!(
+            "database file \"{file_path}\" already exists",
+            file_path = file_path.display()
+        );
+    }
+    let options = SqliteConnectOptions::new()
+        .filename(file_path)
+        .create_if_missing(true);
+    let pool = SqlitePool::connect_with(options).await?;
+    let info = initialize_database(&pool, uuid).await?;
+    Ok((pool, info))
+}
+
+/// Creates a new, empty database in memory.
+///
+/// The database is initialized like [`create_database()`] with a random UUID.
+///
+/// Each connection to an in-memory database opens a separate database.
+/// The returned pool is therefore limited to a single connection that
+/// is kept open until the pool is closed.
+pub async fn create_test_database() -> anyhow::Result<(SqlitePool, DbUuid)> {
+    let options = SqliteConnectOptions::from_str("sqlite::memory:")?;
+    let pool = SqlitePoolOptions::new()
+        .min_connections(1)
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await?;
+    let uuid = DbUuid::new_random();
+    let info = initialize_database(&pool, uuid).await?;
+    debug_assert_eq!(*info.uuid(), uuid);
+    Ok((pool, uuid))
+}
+
+/// Creates all tables and inserts the initial rows into an empty database.
+async fn initialize_database(pool: &SqlitePool, uuid: DbUuid) -> anyhow::Result<Information> {
+    let mut tx = pool.begin().await?;
+    sqlx::raw_sql(SCHEMA_SQL).execute(&mut *tx).await?;
+    Information::create(&mut *tx, uuid, SchemaVersion::LATEST).await?;
+    // Insert NULL album art.
+    let insert_result =
+        sqlx::query(r#"INSERT INTO "AlbumArt" ("hash","albumArt") VALUES (NULL,NULL)"#)
+            .execute(&mut *tx)
+            .await?;
+    debug_assert_eq!(insert_result.last_insert_rowid(), 1);
+    tx.commit().await?;
+
+    let info = Information::load(&mut *pool.acquire().await?).await?;
+    Ok(info)
+}
@@ -0,0 +1,198 @@
+-- SPDX-FileCopyrightText: The endjine authors
+-- SPDX-License-Identifier: MPL-2.0
+
+-- Schema of a new, empty database for testing.
+--
+-- Reverse-engineered from existing databases. Only covers the tables
+-- and columns that are known and required. This is an approximation
+-- and differs from the actual schema of Engine DJ, e.g. the playlist
+-- hierarchy is derived by views instead of tables that are maintained
+-- by triggers. Engine DJ must not open databases with this schema.
+
+CREATE TABLE "Information" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "uuid" TEXT,
+    "schemaVersionMajor" INTEGER,
+    "schemaVersionMinor" INTEGER,
+    "schemaVersionPatch" INTEGER,
+    "currentPlayedIndiciator" INTEGER,
+    "lastRekordBoxLibraryImportReadCounter" INTEGER
+);
+
+CREATE TABLE "AlbumArt" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "hash" TEXT,
+    "albumArt" BLOB
+);
+
+CREATE INDEX "index_AlbumArt_hash" ON "AlbumArt" ("hash");
+
+CREATE TABLE "Track" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "playOrder" INTEGER,
+    "length" INTEGER,
+    "bpm" INTEGER,
+    "year" INTEGER,
+    "path" TEXT,
+    "filename" TEXT,
+    "bitrate" INTEGER,
+    "bpmAnalyzed" REAL,
+    "albumArtId" INTEGER NOT NULL DEFAULT 1,
+    "fileBytes" INTEGER,
+    "title" TEXT,
+    "artist" TEXT,
+    "album" TEXT,
+    "genre" TEXT,
+    "comment" TEXT,
+    "label" TEXT,
+    "composer" TEXT,
+    "remixer" TEXT,
+    "key" INTEGER,
+    "rating" INTEGER,
+    "albumArt" TEXT,
+    "timeLastPlayed" DATETIME,
+    "isPlayed" BOOLEAN NOT NULL DEFAULT FALSE,
+    "fileType" TEXT,
+    "isAnalyzed" BOOLEAN NOT NULL DEFAULT FALSE,
+    "dateCreated" DATETIME NOT NULL DEFAULT (CAST(strftime('%s') AS INTEGER)),
+    "dateAdded" DATETIME NOT NULL DEFAULT (CAST(strftime('%s') AS INTEGER)),
+    "isAvailable" BOOLEAN NOT NULL DEFAULT TRUE,
+    "isMetadataOfPackedTrackChanged" BOOLEAN NOT NULL DEFAULT FALSE,
+    -- The typo in the column name is intended.
+    "isPerfomanceDataOfPackedTrackChanged" BOOLEAN NOT NULL DEFAULT FALSE,
+    "playedIndicator" INTEGER,
+    "isMetadataImported" BOOLEAN NOT NULL DEFAULT FALSE,
+    "pdbImportKey" INTEGER,
+    "streamingSource" TEXT,
+    "uri" TEXT,
+    "isBeatGridLocked" BOOLEAN NOT NULL DEFAULT FALSE,
+    "originDatabaseUuid" TEXT,
+    "originTrackId" INTEGER,
+    "streamingFlags" INTEGER NOT NULL DEFAULT 0,
+    "explicitLyrics" BOOLEAN NOT NULL DEFAULT FALSE,
+    "lastEditTime" DATETIME NOT NULL DEFAULT (CAST(strftime('%s') AS INTEGER))
+);
+
+CREATE INDEX "index_Track_path" ON "Track" ("path");
+
+CREATE INDEX "index_Track_albumArtId" ON "Track" ("albumArtId");
+
+-- Tracks that originate from the local database reference themselves.
+CREATE TRIGGER "trigger_after_insert_Track_origin"
+AFTER INSERT ON "Track"
+FOR EACH ROW
+WHEN NEW."originDatabaseUuid" IS NULL OR NEW."originTrackId" IS NULL
+BEGIN
+    UPDATE "Track"
+    SET "originDatabaseUuid"=(SELECT "uuid" FROM "Information" LIMIT 1),
+        "originTrackId"=NEW."id"
+    WHERE "id"=NEW."id";
+END;
+
+CREATE TABLE "PerformanceData" (
+    "trackId" INTEGER PRIMARY KEY REFERENCES "Track" ("id") ON DELETE CASCADE,
+    "trackData" BLOB,
+    "overviewWaveFormData" BLOB,
+    "beatData" BLOB,
+    "quickCues" BLOB,
+    "loops" BLOB,
+    "thirdPartySourceId" INTEGER,
+    "activeOnLoadLoops" INTEGER
+);
+
+CREATE TABLE "Playlist" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "title" TEXT,
+    "parentListId" INTEGER,
+    "isPersisted" BOOLEAN,
+    "nextListId" INTEGER,
+    "lastEditTime" DATETIME,
+    "isExplicitlyExported" BOOLEAN,
+    UNIQUE ("title", "parentListId")
+);
+
+CREATE TABLE "PlaylistEntity" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "listId" INTEGER REFERENCES "Playlist" ("id") ON DELETE CASCADE,
+    "trackId" INTEGER,
+    "databaseUuid" TEXT,
+    "nextEntityId" INTEGER,
+    "membershipReference" INTEGER,
+    UNIQUE ("listId", "databaseUuid", "trackId")
+);
+
+CREATE INDEX "index_PlaylistEntity_listId" ON "PlaylistEntity" ("listId");
+
+-- The playlist hierarchy is derived from "Playlist" instead of
+-- being maintained by triggers.
+
+CREATE VIEW "PlaylistAllParent" ("id", "parentListId") AS
+WITH RECURSIVE "Ancestor" ("id", "parentListId") AS (
+    SELECT "id", "parentListId" FROM "Playlist" WHERE "parentListId">0
+    UNION
+    SELECT "Ancestor"."id", "Playlist"."parentListId"
+    FROM "Ancestor" JOIN "Playlist" ON "Playlist"."id"="Ancestor"."parentListId"
+    WHERE "Playlist"."parentListId">0
+)
+SELECT "id", "parentListId" FROM "Ancestor";
+
+CREATE VIEW "PlaylistAllChildren" ("id", "childListId") AS
+SELECT "parentListId", "id" FROM "PlaylistAllParent";
+
+CREATE VIEW "PlaylistPath" ("id", "path", "position") AS
+WITH RECURSIVE "Path" ("id", "path") AS (
+    SELECT "id", "title" || ';' FROM "Playlist" WHERE "parentListId"=0
+    UNION ALL
+    SELECT "Playlist"."id", "Path"."path" || "Playlist"."title" || ';'
+    FROM "Path" JOIN "Playlist" ON "Playlist"."parentListId"="Path"."id"
+)
+SELECT "id", "path", ROW_NUMBER() OVER (ORDER BY "path") FROM "Path";
+
+CREATE TABLE "Smartlist" (
+    "listUuid" TEXT NOT NULL PRIMARY KEY,
+    "title" TEXT,
+    "parentPlaylistPath" TEXT,
+    "nextPlaylistPath" TEXT,
+    "nextListUuid" TEXT,
+    "rules" TEXT,
+    "lastEditTime" DATETIME
+);
+
+CREATE TABLE "PreparelistEntity" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "trackId" INTEGER REFERENCES "Track" ("id") ON DELETE CASCADE,
+    "trackNumber" INTEGER
+);
+
+CREATE TABLE "Historylist" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "sessionId" INTEGER,
+    "title" TEXT,
+    "startTime" INTEGER,
+    "timezone" TEXT,
+    "originDriveName" TEXT,
+    "originDatabaseId" TEXT,
+    "originListId" INTEGER,
+    "isDeleted" BOOLEAN DEFAULT FALSE,
+    "editTime" INTEGER
+);
+
+CREATE TABLE "HistorylistEntity" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "listId" INTEGER,
+    "trackId" INTEGER,
+    "startTime" DATETIME
+);
+
+CREATE TABLE "ChangeLog" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "trackId" INTEGER
+);
+
+CREATE TABLE "Pack" (
+    "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "packId" TEXT,
+    "changeLogDatabaseUuid" TEXT,
+    "changeLogId" INTEGER,
+    "lastPackTime" DATETIME
+);
@@ -1,14 +1,16 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
 use anyhow::bail;
-use futures_util::stream::BoxStream;
+use futures_util::{StreamExt as _, TryStreamExt as _, stream::BoxStream};
 use relative_path::RelativePath;
-use sqlx::{FromRow, SqliteExecutor};
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqliteConnection, SqliteExecutor};
 
-use crate::{AlbumArtId, DbUuid, FilePath, LibraryPath, UnixTimestamp};
+use crate::{
+    AlbumArt, AlbumArtId, Bpm, DbUuid, FilePath, LibraryPath, MusicalKey, Rating, UnixTimestamp,
+};
 
 crate::db_id!(TrackId);
 
@@ -21,6 +23,7 @@ crate::db_id!(TrackId);
 pub struct Track {
     pub id: TrackId,
     pub play_order: Option<i64>,
+    /// Duration in seconds.
     pub length: Option<u64>,
     pub bpm: Option<i64>,
     pub year: Option<i64>,
@@ -39,6 +42,10 @@ pub struct Track {
     pub composer: Option<String>,
     pub remixer: Option<String>,
     pub key: Option<u8>,
+    /// Rating as a percentage in the range 0..=100.
+    ///
+    /// Engine DJ stores 20 percent per star instead of the number of stars.
+    /// See [`Rating`] for decoding.
     pub rating: Option<i64>,
     pub album_art: Option<String>,
     pub time_last_played: Option<UnixTimestamp>,
@@ -67,6 +74,44 @@ pub struct Track {
     pub last_edit_time: UnixTimestamp,
 }
 
+/// Tag metadata of a [`Track`].
+///
+/// Usually imported from the track file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackMetadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub genre: Option<String>,
+    pub comment: Option<String>,
+    pub label: Option<String>,
+    pub composer: Option<String>,
+    pub remixer: Option<String>,
+    pub year: Option<i64>,
+}
+
+/// Criteria for filtering [`Track`]s.
+///
+/// All criteria are optional and combined with AND. Text criteria match
+/// substrings case-insensitively (ASCII only).
+#[derive(Debug, Clone, Default)]
+pub struct TrackFilter {
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub genre: Option<String>,
+    /// Inclusive lower bound of the analyzed BPM.
+    pub bpm_min: Option<i64>,
+    /// Inclusive upper bound of the analyzed BPM.
+    pub bpm_max: Option<i64>,
+    /// Inclusive lower bound of the rating.
+    ///
+    /// Matches all percentages that decode to at least the given number
+    /// of stars, see also [`Track::fetch_by_rating()`].
+    pub rating_min: Option<Rating>,
+    /// Only include analyzed tracks.
+    pub analyzed_only: bool,
+}
+
 impl Track {
     #[must_use]
     pub const fn to_ref(&self) -> TrackRef {
@@ -84,6 +129,41 @@ impl Track {
     }
 }
 
+/// A [`Track`] together with its [`AlbumArt`].
+#[derive(Debug, Clone)]
+pub struct TrackWithAlbumArt {
+    pub track: Track,
+    /// `None` if the referenced [`AlbumArt`] does not exist.
+    pub album_art: Option<AlbumArt>,
+}
+
+/// Result row of joining `Track` with `AlbumArt`.
+///
+/// The columns of `AlbumArt` are renamed to avoid conflicts.
+#[derive(FromRow)]
+#[sqlx(rename_all = "camelCase")]
+struct TrackWithAlbumArtRow {
+    #[sqlx(flatten)]
+    track: Track,
+    joined_album_art_id: Option<AlbumArtId>,
+    joined_album_art_hash: Option<String>,
+    joined_album_art_image_data: Option<Vec<u8>>,
+}
+
+impl From<TrackWithAlbumArtRow> for TrackWithAlbumArt {
+    fn from(from: TrackWithAlbumArtRow) -> Self {
+        let TrackWithAlbumArtRow {
+            track,
+            joined_album_art_id,
+            joined_album_art_hash,
+            joined_album_art_image_data,
+        } = from;
+        let album_art = joined_album_art_id
+            .map(|id| AlbumArt::new(id, joined_album_art_hash, joined_album_art_image_data));
+        Self { track, album_art }
+    }
+}
+
 /// References a track within the local and its origin database.
 ///
 /// Subset of fields/columns from [`Track`].
@@ -96,6 +176,11 @@ pub struct TrackRef {
 }
 
 impl TrackRef {
+    #[must_use]
+    pub const fn id(&self) -> TrackId {
+        self.id
+    }
+
     pub fn to_origin(&self, local_db_uuid: DbUuid) -> anyhow::Result<OriginTrackRef> {
         let Self {
             id,
@@ -127,6 +212,34 @@ impl Track {
     /// the column value could safely be set to NULL.
     pub const DEFAULT_ALBUM_ART: &str = "image://planck/0";
 
+    /// Decodes the musical key.
+    ///
+    /// Returns `None` if the key is NULL or invalid.
+    #[must_use]
+    pub fn musical_key(&self) -> Option<MusicalKey> {
+        self.key.and_then(|key| MusicalKey::try_from(key).ok())
+    }
+
+    /// Gets the analyzed tempo.
+    ///
+    /// Returns `None` if the tempo is NULL or invalid, i.e. not positive.
+    #[must_use]
+    pub fn analyzed_bpm(&self) -> Option<Bpm> {
+        self.bpm_analyzed.and_then(Bpm::try_new)
+    }
+
+    /// Display name for playlist files, i.e. "Artist - Title".
+    ///
+    /// Returns `None` if both artist and title are missing.
+    #[must_use]
+    pub(crate) fn display_name(&self) -> Option<String> {
+        match (self.artist.as_deref(), self.title.as_deref()) {
+            (Some(artist), Some(title)) => Some(format!("{artist} - {title}")),
+            (Some(name), None) | (None, Some(name)) => Some(name.to_owned()),
+            (None, None) => None,
+        }
+    }
+
     /// Determines the file path given the library path.
     #[must_use]
     pub fn to_file_path(&self, library_path: &LibraryPath) -> Option<FilePath<'static>> {
@@ -147,6 +260,294 @@ impl Track {
         sqlx::query_as(r#"SELECT * FROM "Track" ORDER BY "id""#).fetch(executor)
     }
 
+    /// Fetches all [`Track`]s together with their [`AlbumArt`] asynchronously.
+    ///
+    /// Unfiltered and in no particular order.
+    #[must_use]
+    pub fn fetch_all_with_album_art<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+    ) -> BoxStream<'a, sqlx::Result<TrackWithAlbumArt>> {
+        sqlx::query_as::<_, TrackWithAlbumArtRow>(
+            r#"SELECT "Track".*,
+               "AlbumArt"."id" AS "joinedAlbumArtId",
+               "AlbumArt"."hash" AS "joinedAlbumArtHash",
+               "AlbumArt"."albumArt" AS "joinedAlbumArtImageData"
+               FROM "Track" LEFT JOIN "AlbumArt" ON "Track"."albumArtId"="AlbumArt"."id"
+               ORDER BY "Track"."id""#,
+        )
+        .fetch(executor)
+        .map_ok(Into::into)
+        .boxed()
+    }
+
+    /// Fetches all [`Track`]s together with their [`AlbumArt`] metadata asynchronously.
+    ///
+    /// Like [`Self::fetch_all_with_album_art()`], but without loading the
+    /// image data. [`AlbumArt::image_data()`] always returns `None`.
+    #[must_use]
+    pub fn fetch_all_with_album_art_metadata<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+    ) -> BoxStream<'a, sqlx::Result<TrackWithAlbumArt>> {
+        sqlx::query_as::<_, TrackWithAlbumArtRow>(
+            r#"SELECT "Track".*,
+               "AlbumArt"."id" AS "joinedAlbumArtId",
+               "AlbumArt"."hash" AS "joinedAlbumArtHash",
+               NULL AS "joinedAlbumArtImageData"
+               FROM "Track" LEFT JOIN "AlbumArt" ON "Track"."albumArtId"="AlbumArt"."id"
+               ORDER BY "Track"."id""#,
+        )
+        .fetch(executor)
+        .map_ok(Into::into)
+        .boxed()
+    }
+
+    /// Counts all [`Track`]s.
+    pub async fn count_all(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
+        let count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "Track""#)
+            .fetch_one(executor)
+            .await?;
+        debug_assert!(count >= 0);
+        Ok(count.cast_unsigned())
+    }
+
+    /// Counts all analyzed [`Track`]s.
+    pub async fn count_analyzed(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
+        let count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "Track" WHERE "isAnalyzed""#)
+            .fetch_one(executor)
+            .await?;
+        debug_assert!(count >= 0);
+        Ok(count.cast_unsigned())
+    }
+
+    /// Counts both the analyzed and all [`Track`]s within a single query.
+    ///
+    /// Returns the tuple `(analyzed, total)`.
+    pub async fn count_analyzed_vs_total(
+        executor: impl SqliteExecutor<'_>,
+    ) -> sqlx::Result<(u64, u64)> {
+        let (analyzed_count, total_count): (i64, i64) = sqlx::query_as(
+            r#"SELECT COALESCE(SUM(CASE WHEN "isAnalyzed" THEN 1 ELSE 0 END),0),COUNT(*) FROM "Track""#,
+        )
+        .fetch_one(executor)
+        .await?;
+        debug_assert!(analyzed_count >= 0);
+        debug_assert!(analyzed_count <= total_count);
+        Ok((analyzed_count.cast_unsigned(), total_count.cast_unsigned()))
+    }
+
+    /// Fetches all [`Track`]s that match the filter asynchronously.
+    ///
+    /// Ordered by ID.
+    #[must_use]
+    pub fn fetch_filtered<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        filter: &'a TrackFilter,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        let TrackFilter {
+            artist,
+            title,
+            genre,
+            bpm_min,
+            bpm_max,
+            rating_min,
+            analyzed_only,
+        } = filter;
+        sqlx::query_as(
+            r#"SELECT * FROM "Track" WHERE
+               (?1 IS NULL OR "artist" LIKE '%' || ?1 || '%') AND
+               (?2 IS NULL OR "title" LIKE '%' || ?2 || '%') AND
+               (?3 IS NULL OR "genre" LIKE '%' || ?3 || '%') AND
+               (?4 IS NULL OR "bpmAnalyzed">=?4) AND
+               (?5 IS NULL OR "bpmAnalyzed"<=?5) AND
+               (?6 IS NULL OR "rating">=?6-10) AND
+               (NOT ?7 OR "isAnalyzed")
+               ORDER BY "id""#,
+        )
+        .bind(artist.as_deref())
+        .bind(title.as_deref())
+        .bind(genre.as_deref())
+        .bind(bpm_min)
+        .bind(bpm_max)
+        .bind(rating_min)
+        .bind(analyzed_only)
+        .fetch(executor)
+    }
+
+    /// Fetches all [`Track`]s with the given musical key asynchronously.
+    ///
+    /// Ordered by ID.
+    #[must_use]
+    pub fn fetch_by_key<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        key: MusicalKey,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "Track" WHERE "key"=?1 ORDER BY "id""#)
+            .bind(u8::from(key))
+            .fetch(executor)
+    }
+
+    /// Fetches all [`Track`]s with the given rating asynchronously.
+    ///
+    /// Matches all percentages that decode to the given number of stars,
+    /// i.e. the same rounding as when decoding a [`Rating`] applies.
+    ///
+    /// Ordered by ID.
+    #[must_use]
+    pub fn fetch_by_rating<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        rating: Rating,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "Track" WHERE "rating" BETWEEN MAX(?1-10,0) AND MIN(?1+9,100) ORDER BY "id""#)
+            .bind(rating)
+            .fetch(executor)
+    }
+
+    /// Fetches all [`Track`]s with the given file type asynchronously.
+    ///
+    /// The file type, e.g. `mp3` or `flac`, is compared case-insensitively.
+    ///
+    /// Ordered by ID.
+    #[must_use]
+    pub fn fetch_by_file_type<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        file_type: &'a str,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "Track" WHERE LOWER("fileType")=LOWER(?1) ORDER BY "id""#)
+            .bind(file_type)
+            .fetch(executor)
+    }
+
+    /// Fetches all [`Track`]s of an artist asynchronously.
+    ///
+    /// The artist is compared case-insensitively (ASCII only).
+    ///
+    /// Ordered by album and title.
+    #[must_use]
+    pub fn fetch_by_artist<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        artist: &'a str,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(
+            r#"SELECT * FROM "Track" WHERE LOWER("artist")=LOWER(?1) ORDER BY "album", "title""#,
+        )
+        .bind(artist)
+        .fetch(executor)
+    }
+
+    /// Fetches all [`Track`]s with an artist that contains the query asynchronously.
+    ///
+    /// Matches substrings case-insensitively (ASCII only) like [`TrackFilter::artist`].
+    ///
+    /// Ordered by artist, album, and title.
+    #[must_use]
+    pub fn search_by_artist<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        query: &'a str,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(
+            r#"SELECT * FROM "Track" WHERE "artist" LIKE '%' || ?1 || '%' ORDER BY "artist", "album", "title""#,
+        )
+        .bind(query)
+        .fetch(executor)
+    }
+
+    /// Searches [`Track`]s by title, artist, album, or comment asynchronously.
+    ///
+    /// Matches substrings case-insensitively (ASCII only). This is not a
+    /// real full-text search, i.e. results are not ranked by relevance and
+    /// each query requires a full table scan. Adequate for small to medium
+    /// sized libraries.
+    ///
+    /// Ordered by ID.
+    #[must_use]
+    pub fn full_text_search<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        query: &'a str,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(
+            r#"SELECT * FROM "Track" WHERE
+               "title" LIKE '%' || ?1 || '%' OR
+               "artist" LIKE '%' || ?1 || '%' OR
+               "album" LIKE '%' || ?1 || '%' OR
+               "comment" LIKE '%' || ?1 || '%'
+               ORDER BY "id""#,
+        )
+        .bind(query)
+        .fetch(executor)
+    }
+
+    /// Fetches all [`Track`]s of an album asynchronously.
+    ///
+    /// The album is compared case-insensitively (ASCII only).
+    ///
+    /// Ordered by artist, play order, i.e. the track number within the album,
+    /// and title.
+    #[must_use]
+    pub fn fetch_by_album<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        album: &'a str,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(
+            r#"SELECT * FROM "Track" WHERE LOWER("album")=LOWER(?1) ORDER BY "artist", "playOrder", "title""#,
+        )
+        .bind(album)
+        .fetch(executor)
+    }
+
+    /// Fetches all [`Track`]s released within a range of years asynchronously.
+    ///
+    /// Both bounds are inclusive.
+    ///
+    /// Ordered by year, artist, and title.
+    #[must_use]
+    pub fn fetch_by_year_range<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        from: i64,
+        to: i64,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(
+            r#"SELECT * FROM "Track" WHERE "year" BETWEEN ?1 AND ?2 ORDER BY "year", "artist", "title""#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch(executor)
+    }
+
+    /// Fetches all [`Track`]s without a year asynchronously.
+    ///
+    /// Ordered by ID.
+    #[must_use]
+    pub fn fetch_without_year<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "Track" WHERE "year" IS NULL ORDER BY "id""#)
+            .fetch(executor)
+    }
+
+    /// Fetches all [`Track`]s with a locked beat grid asynchronously.
+    ///
+    /// Ordered by ID.
+    #[must_use]
+    pub fn fetch_beat_grid_locked<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "Track" WHERE "isBeatGridLocked" ORDER BY "id""#)
+            .fetch(executor)
+    }
+
+    /// Fetches all [`Track`]s with or without explicit lyrics asynchronously.
+    ///
+    /// Ordered by ID.
+    #[must_use]
+    pub fn fetch_explicit<'a>(
+        executor: impl SqliteExecutor<'a> + 'a,
+        explicit: bool,
+    ) -> BoxStream<'a, sqlx::Result<Self>> {
+        sqlx::query_as(r#"SELECT * FROM "Track" WHERE "explicitLyrics"=?1 ORDER BY "id""#)
+            .bind(explicit)
+            .fetch(executor)
+    }
+
     /// Loads a single [`Track`] by ID.
     ///
     /// Returns `Ok(None)` if the requested [`Track`] has not been found.
@@ -160,6 +561,49 @@ impl Track {
             .await
     }
 
+    /// Loads multiple [`Track`]s by ID.
+    ///
+    /// Returns the results in the order of the given IDs. Missing
+    /// [`Track`]s are returned as `None`. The IDs are queried in chunks
+    /// to stay within the limit of bound parameters per statement.
+    pub async fn find_many_by_id(
+        connection: &mut SqliteConnection,
+        ids: &[TrackId],
+    ) -> sqlx::Result<Vec<Option<Self>>> {
+        const MAX_IDS_PER_QUERY: usize = 999;
+        let mut tracks_by_id = HashMap::with_capacity(ids.len());
+        for ids_chunk in ids.chunks(MAX_IDS_PER_QUERY) {
+            let mut query_builder =
+                QueryBuilder::<Sqlite>::new(r#"SELECT * FROM "Track" WHERE "id" IN ("#);
+            let mut separated = query_builder.separated(',');
+            for id in ids_chunk {
+                separated.push_bind(*id);
+            }
+            query_builder.push(")");
+            let tracks: Vec<Self> = query_builder
+                .build_query_as()
+                .fetch_all(&mut *connection)
+                .await?;
+            tracks_by_id.extend(tracks.into_iter().map(|track| (track.id, track)));
+        }
+        Ok(ids.iter().map(|id| tracks_by_id.get(id).cloned()).collect())
+    }
+
+    /// Loads the [`TrackRef`] of a single [`Track`] by ID.
+    ///
+    /// Returns `Ok(None)` if the requested [`Track`] has not been found.
+    pub async fn try_load_ref(
+        executor: impl SqliteExecutor<'_>,
+        id: TrackId,
+    ) -> sqlx::Result<Option<TrackRef>> {
+        sqlx::query_as(
+            r#"SELECT "id","originDatabaseUuid","originTrackId" FROM "Track" WHERE "id"=?1"#,
+        )
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+    }
+
     /// Reset unused default album art for tracks with album art.
     pub async fn reset_unused_default_album_art(
         executor: impl SqliteExecutor<'_>,
@@ -171,6 +615,110 @@ impl Track {
         Ok(result.rows_affected())
     }
 
+    /// Updates the tag metadata of a single [`Track`].
+    ///
+    /// Only overwrites metadata fields with values, i.e. `None` values
+    /// preserve the existing column values. Marks the metadata as imported.
+    ///
+    /// Returns `false` if the requested [`Track`] has not been found.
+    pub async fn update_metadata(
+        executor: impl SqliteExecutor<'_>,
+        id: TrackId,
+        metadata: &TrackMetadata,
+    ) -> sqlx::Result<bool> {
+        let TrackMetadata {
+            title,
+            artist,
+            album,
+            genre,
+            comment,
+            label,
+            composer,
+            remixer,
+            year,
+        } = metadata;
+        let result = sqlx::query(
+            r#"UPDATE "Track" SET
+               "title"=COALESCE(?2,"title"),
+               "artist"=COALESCE(?3,"artist"),
+               "album"=COALESCE(?4,"album"),
+               "genre"=COALESCE(?5,"genre"),
+               "comment"=COALESCE(?6,"comment"),
+               "label"=COALESCE(?7,"label"),
+               "composer"=COALESCE(?8,"composer"),
+               "remixer"=COALESCE(?9,"remixer"),
+               "year"=COALESCE(?10,"year"),
+               "isMetadataImported"=TRUE,
+               "lastEditTime"=CAST(strftime('%s') AS INTEGER)
+               WHERE "id"=?1"#,
+        )
+        .bind(id)
+        .bind(title)
+        .bind(artist)
+        .bind(album)
+        .bind(genre)
+        .bind(comment)
+        .bind(label)
+        .bind(composer)
+        .bind(remixer)
+        .bind(year)
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Marks a single [`Track`] as available or unavailable.
+    ///
+    /// Returns `false` if the requested [`Track`] has not been found.
+    pub async fn update_availability(
+        executor: impl SqliteExecutor<'_>,
+        id: TrackId,
+        available: bool,
+    ) -> sqlx::Result<bool> {
+        let result = sqlx::query(r#"UPDATE "Track" SET "isAvailable"=?1 WHERE "id"=?2"#)
+            .bind(available)
+            .bind(id)
+            .execute(executor)
+            .await?;
+        debug_assert!(result.rows_affected() <= 1);
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Locks or unlocks the beat grid of a single [`Track`].
+    ///
+    /// Engine DJ does not overwrite a locked beat grid when re-analyzing
+    /// the track.
+    ///
+    /// Returns `false` if the requested [`Track`] has not been found.
+    pub async fn set_beat_grid_locked(
+        executor: impl SqliteExecutor<'_>,
+        id: TrackId,
+        locked: bool,
+    ) -> sqlx::Result<bool> {
+        let result = sqlx::query(
+            r#"UPDATE "Track" SET
+               "isBeatGridLocked"=?2,
+               "lastEditTime"=CAST(strftime('%s') AS INTEGER)
+               WHERE "id"=?1"#,
+        )
+        .bind(id)
+        .bind(locked)
+        .execute(executor)
+        .await?;
+        debug_assert!(result.rows_affected() <= 1);
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Marks all [`Track`]s as unavailable.
+    ///
+    /// Returns the number of tracks that were previously available.
+    pub async fn mark_all_unavailable(executor: impl SqliteExecutor<'_>) -> sqlx::Result<u64> {
+        let result = sqlx::query(r#"UPDATE "Track" SET "isAvailable"=FALSE WHERE "isAvailable""#)
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
     /// Finds the [`TrackRef`] for the given path.
     ///
     /// The path must be relative and match the path in the database.
@@ -189,7 +737,7 @@ impl Track {
 }
 
 /// Parent directory of "Engine Library".
-const RELATIVE_TRACK_PATH_PREFIX: &str = "..";
+pub(crate) const RELATIVE_TRACK_PATH_PREFIX: &str = "..";
 
 pub fn import_track_file_path<'p>(
     library_path: &LibraryPath,
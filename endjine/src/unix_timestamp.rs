@@ -1,6 +1,8 @@
 // SPDX-FileCopyrightText: The endjine authors
 // SPDX-License-Identifier: MPL-2.0
 
+use std::time::SystemTime;
+
 use sqlx::{
     Decode, Encode, Sqlite,
     encode::IsNull,
@@ -11,13 +13,53 @@ use sqlx::{
 
 /// UNIX timestamp.
 ///
-/// Encoded as integer seconds since epoch origin.
+/// Encoded as integer seconds since epoch origin, both in the database
+/// and when (de-)serialized.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 #[repr(transparent)]
 pub struct UnixTimestamp {
     pub seconds_since_epoch_origin: i64,
 }
 
+impl UnixTimestamp {
+    /// Current system time.
+    ///
+    /// Truncated to whole seconds.
+    #[must_use]
+    pub fn now() -> Self {
+        Self::from_datetime(OffsetDateTime::from(SystemTime::now()))
+    }
+
+    /// Converts a date/time.
+    ///
+    /// Truncated to whole seconds.
+    #[must_use]
+    pub const fn from_datetime(dt: OffsetDateTime) -> Self {
+        Self {
+            seconds_since_epoch_origin: dt.unix_timestamp(),
+        }
+    }
+
+    /// Converts into a UTC date/time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the timestamp is out of the range supported by [`OffsetDateTime`].
+    #[must_use]
+    pub fn to_datetime(self) -> OffsetDateTime {
+        let Self {
+            seconds_since_epoch_origin,
+        } = self;
+        OffsetDateTime::from_unix_timestamp(seconds_since_epoch_origin)
+            .expect("timestamp in valid range")
+    }
+}
+
 // SQLx integration: Derive implementations using transparent repr
 impl sqlx::Type<Sqlite> for UnixTimestamp {
     fn type_info() -> SqliteTypeInfo {
@@ -47,3 +89,34 @@ impl<'q> Encode<'q, Sqlite> for UnixTimestamp {
         <i64 as Encode<Sqlite>>::encode_by_ref(seconds_since_epoch_origin, buf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::types::time::OffsetDateTime;
+
+    use super::UnixTimestamp;
+
+    #[test]
+    fn datetime_roundtrip() {
+        let dt = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let ts = UnixTimestamp::from_datetime(dt);
+        assert_eq!(1_700_000_000, ts.seconds_since_epoch_origin);
+        assert_eq!(dt, ts.to_datetime());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_roundtrip() {
+        let ts = UnixTimestamp {
+            seconds_since_epoch_origin: 1_700_000_000,
+        };
+        let json = serde_json::to_string(&ts).unwrap();
+        assert_eq!("1700000000", json);
+        assert_eq!(ts, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn now_after_epoch_origin() {
+        assert!(UnixTimestamp::now().seconds_since_epoch_origin > 0);
+    }
+}
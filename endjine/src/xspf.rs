@@ -0,0 +1,228 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::{borrow::Cow, fs, io::Write, path::Path};
+
+use anyhow::{Context as _, bail};
+use roxmltree::{Document, Node};
+use sqlx::SqlitePool;
+
+use crate::{
+    FilePath, LibraryPath, Playlist, PlaylistEntity, PlaylistId,
+    file_uri::{file_path_from_uri, file_path_to_uri, is_uri, percent_decode},
+};
+
+const XSPF_NAMESPACE: &str = "http://xspf.org/ns/0/";
+
+/// Imports the file paths from an XSPF playlist file.
+///
+/// Relative locations are resolved against `base_path` that defaults
+/// to the parent directory of the XSPF file.
+pub fn import_xspf_file(
+    path: &Path,
+    base_path: Option<&Path>,
+) -> anyhow::Result<Vec<FilePath<'static>>> {
+    let xml = fs::read_to_string(path).context("read XSPF file")?;
+    let base_path = base_path.or_else(|| path.parent());
+    read_xspf(&xml, base_path)
+}
+
+/// Reads the file paths from an XSPF playlist.
+///
+/// Evaluates the first `<location>` of each `<track>` in the `<trackList>`.
+/// Tracks without a location are skipped.
+///
+/// Relative locations are resolved against `base_path`. Fails if no
+/// `base_path` is given and the playlist contains relative locations.
+pub fn read_xspf(xml: &str, base_path: Option<&Path>) -> anyhow::Result<Vec<FilePath<'static>>> {
+    let document = Document::parse(xml).context("parse XML")?;
+    let playlist = document.root_element();
+    if !playlist.has_tag_name("playlist") {
+        bail!(
+            "unexpected root element <{tag_name}>",
+            tag_name = playlist.tag_name().name()
+        );
+    }
+    let Some(track_list) = find_child_element(playlist, "trackList") else {
+        bail!("missing <trackList>");
+    };
+    track_list
+        .children()
+        .filter(|node| node.has_tag_name("track"))
+        .filter_map(|track| {
+            let location = find_child_element(track, "location")?;
+            Some(import_xspf_location(
+                location.text().unwrap_or_default().trim(),
+                base_path,
+            ))
+        })
+        .collect()
+}
+
+fn find_child_element<'a, 'input>(
+    parent: Node<'a, 'input>,
+    name: &str,
+) -> Option<Node<'a, 'input>> {
+    parent.children().find(|node| node.has_tag_name(name))
+}
+
+fn import_xspf_location(
+    location: &str,
+    base_path: Option<&Path>,
+) -> anyhow::Result<FilePath<'static>> {
+    if location.is_empty() {
+        bail!("empty location");
+    }
+    if is_uri(location) {
+        let file_path = file_path_from_uri(location)?;
+        return Ok(FilePath::import_path(&file_path));
+    }
+    // Relative URI reference.
+    let relative_path =
+        percent_decode(location).with_context(|| format!("decode location \"{location}\""))?;
+    let Some(base_path) = base_path else {
+        bail!("unresolved relative location \"{location}\"");
+    };
+    Ok(FilePath::import_path(&base_path.join(relative_path)))
+}
+
+/// Exports the tracks of a playlist as XSPF.
+///
+/// Writes the location as a `file://` URI together with the title,
+/// creator (artist), album, and duration of each track. Entries that
+/// reference missing tracks or tracks without a path are skipped.
+///
+/// Returns the number of tracks written.
+pub async fn export_playlist_to_xspf(
+    pool: &SqlitePool,
+    playlist_id: PlaylistId,
+    library_path: &LibraryPath,
+    mut output: impl Write,
+) -> anyhow::Result<u64> {
+    let Some(playlist) = Playlist::try_load(pool, playlist_id).await? else {
+        bail!("playlist {playlist_id} not found");
+    };
+    let tracks =
+        PlaylistEntity::load_list_tracks_with_file_paths(pool, playlist_id, library_path).await?;
+    writeln!(output, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(output, r#"<playlist version="1" xmlns="{XSPF_NAMESPACE}">"#)?;
+    writeln!(
+        output,
+        "  <title>{title}</title>",
+        title = escape_xml(&playlist.title)
+    )?;
+    writeln!(output, "  <trackList>")?;
+    let mut track_count = 0;
+    for (track, file_path) in tracks {
+        let location = file_path_to_uri(&file_path.to_path())
+            .with_context(|| format!("location of track {track_id}", track_id = track.id))?;
+        writeln!(output, "    <track>")?;
+        writeln!(
+            output,
+            "      <location>{}</location>",
+            escape_xml(&location)
+        )?;
+        for (tag_name, text) in [
+            ("title", &track.title),
+            ("creator", &track.artist),
+            ("album", &track.album),
+        ] {
+            if let Some(text) = text {
+                writeln!(
+                    output,
+                    "      <{tag_name}>{}</{tag_name}>",
+                    escape_xml(text)
+                )?;
+            }
+        }
+        if let Some(length) = track.length {
+            // XSPF expects the duration in milliseconds.
+            let duration_millis = length.saturating_mul(1000);
+            writeln!(output, "      <duration>{duration_millis}</duration>")?;
+        }
+        writeln!(output, "    </track>")?;
+        track_count += 1;
+    }
+    writeln!(output, "  </trackList>")?;
+    writeln!(output, "</playlist>")?;
+    output.flush()?;
+    Ok(track_count)
+}
+
+fn escape_xml(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::FilePath;
+
+    use super::{escape_xml, read_xspf};
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn read_track_locations() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <title>Test</title>
+  <trackList>
+    <track>
+      <location>file:///music/a%20b.mp3</location>
+      <title>A</title>
+    </track>
+    <track>
+      <title>Without location</title>
+    </track>
+    <track>
+      <location>sub/c%26d.mp3</location>
+    </track>
+  </trackList>
+</playlist>"#;
+        let file_paths = read_xspf(xml, Some(Path::new("/base"))).unwrap();
+        assert_eq!(
+            file_paths,
+            [
+                FilePath::import_path("/music/a b.mp3"),
+                FilePath::import_path("/base/sub/c&d.mp3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_invalid() {
+        assert!(read_xspf("", None).is_err());
+        assert!(read_xspf("<playlist>", None).is_err());
+        assert!(read_xspf("<foo><trackList/></foo>", None).is_err());
+        assert!(read_xspf("<playlist/>", None).is_err());
+        assert!(
+            read_xspf(
+                "<playlist><trackList><track><location>a.mp3</location></track></trackList></playlist>",
+                None
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn escape_xml_special_chars() {
+        assert_eq!(escape_xml("Rock & Roll"), "Rock &amp; Roll");
+        assert_eq!(escape_xml("<\"'>"), "&lt;&quot;&apos;&gt;");
+        assert_eq!(escape_xml("plain"), "plain");
+    }
+}
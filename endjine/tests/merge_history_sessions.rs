@@ -0,0 +1,51 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use sqlx::SqlitePool;
+
+use endjine::{
+    HistorylistEntity, HistorylistId, batch::merge_history_sessions, testing::create_test_database,
+};
+
+async fn insert_session(pool: &SqlitePool, id: i64, entries: &[(i64, i64)]) -> anyhow::Result<()> {
+    sqlx::query(r#"INSERT INTO "Historylist" ("id","sessionId","startTime") VALUES (?1,?1,0)"#)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    for (track_id, start_time) in entries {
+        sqlx::query(
+            r#"INSERT INTO "HistorylistEntity" ("listId","trackId","startTime") VALUES (?1,?2,?3)"#,
+        )
+        .bind(id)
+        .bind(track_id)
+        .bind(start_time)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn merge_into_target_with_duplicates() -> anyhow::Result<()> {
+    let (pool, _) = create_test_database().await?;
+    // The target already contains a duplicate entry.
+    insert_session(&pool, 1, &[(1, 100), (1, 100), (2, 200)]).await?;
+    // One entry of the source is a duplicate of an entry in the target.
+    insert_session(&pool, 2, &[(2, 200), (3, 300), (4, 400)]).await?;
+    let target_id = HistorylistId::new(1);
+    let moved_count = merge_history_sessions(&pool, &[HistorylistId::new(2)], target_id).await?;
+    assert_eq!(2, moved_count);
+    assert_eq!(4, HistorylistEntity::count_list(&pool, target_id).await?);
+    assert_eq!(
+        0,
+        HistorylistEntity::count_list(&pool, HistorylistId::new(2)).await?
+    );
+    Ok(())
+}
+
+#[test]
+fn merge_history_sessions_counts_moved_entries() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime
+        .block_on(merge_into_target_with_duplicates())
+        .unwrap();
+}
@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::collections::HashSet;
+
+use futures_util::TryStreamExt as _;
+use proptest::prelude::*;
+use sqlx::SqlitePool;
+
+use endjine::{
+    DbUuid, OriginTrackRef, Playlist, PlaylistEntity, PlaylistEntityId, PlaylistId, TrackId,
+    testing::create_test_database,
+};
+
+const TRACK_COUNT: i64 = 16;
+
+async fn insert_tracks(pool: &SqlitePool) -> anyhow::Result<()> {
+    for i in 1..=TRACK_COUNT {
+        sqlx::query(r#"INSERT INTO "Track" ("id","path") VALUES (?1,?2)"#)
+            .bind(i)
+            .bind(format!("../Music/{i}.mp3"))
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+fn track_refs(db_uuid: DbUuid, track_ids: &[i64]) -> Vec<OriginTrackRef> {
+    track_ids
+        .iter()
+        .map(|&id| OriginTrackRef {
+            id: TrackId::new(id),
+            db_uuid,
+        })
+        .collect()
+}
+
+/// Unique track ids in the order of their first occurrence.
+fn unique_track_ids(track_ids: &[i64]) -> Vec<i64> {
+    let mut visited = HashSet::new();
+    track_ids
+        .iter()
+        .copied()
+        .filter(|id| visited.insert(*id))
+        .collect()
+}
+
+async fn assert_list_invariants(
+    pool: &SqlitePool,
+    list_id: PlaylistId,
+    expected_track_ids: &[i64],
+) -> anyhow::Result<()> {
+    let entries = PlaylistEntity::load_list(pool, list_id).await?;
+    let chain = PlaylistEntity::fetch_list_ordered_by_chain(pool, list_id)
+        .try_collect::<Vec<_>>()
+        .await?;
+    let entry_ids = entries.iter().map(|entry| entry.id).collect::<Vec<_>>();
+    let chain_ids = chain.iter().map(|entry| entry.id).collect::<Vec<_>>();
+    assert_eq!(entry_ids, chain_ids);
+    let track_ids = entries
+        .iter()
+        .map(|entry| entry.track_id.to_raw())
+        .collect::<Vec<_>>();
+    assert_eq!(track_ids, expected_track_ids);
+    let last_count = entries
+        .iter()
+        .filter(|entry| entry.next_entity_id == PlaylistEntityId::INVALID_ZERO)
+        .count();
+    assert_eq!(last_count, usize::from(!entries.is_empty()));
+    let membership_refs = entries
+        .iter()
+        .map(|entry| entry.membership_reference)
+        .collect::<HashSet<_>>();
+    assert_eq!(membership_refs.len(), entries.len());
+    Ok(())
+}
+
+async fn append_and_replace(
+    appended_track_ids: &[i64],
+    replaced_track_ids: &[i64],
+) -> anyhow::Result<()> {
+    let (pool, db_uuid) = create_test_database().await?;
+    insert_tracks(&pool).await?;
+    let mut tx = pool.begin().await?;
+    let (list_id, _) = Playlist::find_or_create_by_path(&mut tx, "Playlist", true).await?;
+    Playlist::append_tracks(&mut tx, list_id, track_refs(db_uuid, appended_track_ids)).await?;
+    tx.commit().await?;
+    assert_list_invariants(&pool, list_id, &unique_track_ids(appended_track_ids)).await?;
+
+    let mut tx = pool.begin().await?;
+    Playlist::replace_tracks(&mut tx, list_id, track_refs(db_uuid, replaced_track_ids)).await?;
+    tx.commit().await?;
+    assert_list_invariants(&pool, list_id, &unique_track_ids(replaced_track_ids)).await?;
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn append_and_replace_tracks(
+        appended_track_ids in prop::collection::vec(1..=TRACK_COUNT, 0..24),
+        replaced_track_ids in prop::collection::vec(1..=TRACK_COUNT, 0..24),
+    ) {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime
+            .block_on(append_and_replace(&appended_track_ids, &replaced_track_ids))
+            .unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use endjine::{
+    DbUuid, FilePath, LibraryPath, OriginTrackRef, Playlist, TrackId, export_playlist_to_pls,
+    testing::create_test_database,
+};
+
+async fn export_tracks_of_external_entries() -> anyhow::Result<()> {
+    let (pool, db_uuid) = create_test_database().await?;
+    let origin_db_uuid = DbUuid::new_random();
+    // The local id of the imported track differs from its origin id,
+    // which in turn collides with the local id of an unrelated track.
+    for (id, path, origin_track_id) in [
+        (1, "../Music/local.mp3", None),
+        (2, "../Music/imported.mp3", Some(1)),
+    ] {
+        sqlx::query(
+            r#"INSERT INTO "Track" ("id","path","originDatabaseUuid","originTrackId") VALUES (?1,?2,?3,?4)"#,
+        )
+        .bind(id)
+        .bind(path)
+        .bind(origin_track_id.map(|_| origin_db_uuid))
+        .bind(origin_track_id)
+        .execute(&pool)
+        .await?;
+    }
+    let mut tx = pool.begin().await?;
+    let (list_id, _) = Playlist::find_or_create_by_path(&mut tx, "Playlist", true).await?;
+    Playlist::append_tracks(
+        &mut tx,
+        list_id,
+        [
+            OriginTrackRef {
+                id: TrackId::new(1),
+                db_uuid: origin_db_uuid,
+            },
+            OriginTrackRef {
+                id: TrackId::new(1),
+                db_uuid,
+            },
+        ],
+    )
+    .await?;
+    tx.commit().await?;
+
+    let library_path = LibraryPath::new(&FilePath::import_path("/Engine Library/Database2/m.db"))?;
+    let mut pls = Vec::new();
+    let entry_count = export_playlist_to_pls(&pool, list_id, &library_path, &mut pls).await?;
+    assert_eq!(entry_count, 2);
+    let pls = String::from_utf8(pls)?;
+    let file_lines = pls
+        .lines()
+        .filter(|line| line.starts_with("File"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        file_lines,
+        ["File1=/Music/imported.mp3", "File2=/Music/local.mp3"]
+    );
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn export_pls_resolves_external_entries_by_origin() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime
+        .block_on(export_tracks_of_external_entries())
+        .unwrap();
+}
@@ -0,0 +1,32 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use endjine::{Smartlist, SmartlistRules, SmartlistRulesMatch, testing::create_test_database};
+
+async fn create_with_parent_paths() -> anyhow::Result<()> {
+    let (pool, _) = create_test_database().await?;
+    let rules = SmartlistRules {
+        r#match: SmartlistRulesMatch::All,
+        rules: vec![],
+        rv: 1,
+    };
+    for parent_path in ["", "Parent;", "Parent;Child;"] {
+        let uuid = Smartlist::create(&pool, "Smartlist", &rules, parent_path).await?;
+        let smartlist = Smartlist::try_load(&pool, &uuid).await?.unwrap();
+        assert_eq!(smartlist.parent_playlist_path, parent_path);
+    }
+    for parent_path in [";Parent", "Parent", "Parent;Child"] {
+        assert!(
+            Smartlist::create(&pool, "Smartlist", &rules, parent_path)
+                .await
+                .is_err()
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn create_smartlist_with_root_or_terminated_parent_path() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(create_with_parent_paths()).unwrap();
+}
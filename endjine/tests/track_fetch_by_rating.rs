@@ -0,0 +1,43 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use futures_util::TryStreamExt as _;
+
+use endjine::{Rating, Track, testing::create_test_database};
+
+async fn fetch_by_rating_matches_decoded_stars() -> anyhow::Result<()> {
+    let (pool, _) = create_test_database().await?;
+    for (id, rating) in [(1, 0), (2, 9), (3, 50), (4, 60), (5, 69), (6, 70), (7, 100)] {
+        sqlx::query(r#"INSERT INTO "Track" ("id","path","rating") VALUES (?1,?2,?3)"#)
+            .bind(id)
+            .bind(format!("../Music/{id}.mp3"))
+            .bind(rating)
+            .execute(&pool)
+            .await?;
+    }
+    for (stars, expected_ids) in [
+        (0, vec![1, 2]),
+        (3, vec![3, 4, 5]),
+        (4, vec![6]),
+        (5, vec![7]),
+    ] {
+        let rating = Rating::try_new(stars).unwrap();
+        let tracks = Track::fetch_by_rating(&pool, rating)
+            .try_collect::<Vec<_>>()
+            .await?;
+        let ids = tracks
+            .iter()
+            .map(|track| track.id.to_raw())
+            .collect::<Vec<_>>();
+        assert_eq!(expected_ids, ids);
+    }
+    Ok(())
+}
+
+#[test]
+fn fetch_by_rating() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime
+        .block_on(fetch_by_rating_matches_decoded_stars())
+        .unwrap();
+}
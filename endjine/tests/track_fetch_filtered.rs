@@ -0,0 +1,58 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use futures_util::TryStreamExt as _;
+use sqlx::SqlitePool;
+
+use endjine::{Rating, Track, TrackFilter, testing::create_test_database};
+
+async fn fetch_filtered_ids(pool: &SqlitePool, filter: &TrackFilter) -> anyhow::Result<Vec<i64>> {
+    let tracks = Track::fetch_filtered(pool, filter)
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(tracks.iter().map(|track| track.id.to_raw()).collect())
+}
+
+async fn fetch_filtered_by_analyzed_bpm_and_stars() -> anyhow::Result<()> {
+    let (pool, _) = create_test_database().await?;
+    // The BPM from the file tags differs from the analyzed BPM.
+    for (id, bpm, bpm_analyzed, rating) in [
+        (1, 90, 120.0, 20),
+        (2, 120, 127.5, 49),
+        (3, 130, 140.0, 50),
+        (4, 140, 90.0, 100),
+    ] {
+        sqlx::query(
+            r#"INSERT INTO "Track" ("id","path","bpm","bpmAnalyzed","rating") VALUES (?1,?2,?3,?4,?5)"#,
+        )
+        .bind(id)
+        .bind(format!("../Music/{id}.mp3"))
+        .bind(bpm)
+        .bind(bpm_analyzed)
+        .bind(rating)
+        .execute(&pool)
+        .await?;
+    }
+    let filter = TrackFilter {
+        bpm_min: Some(120),
+        bpm_max: Some(130),
+        ..Default::default()
+    };
+    assert_eq!(vec![1, 2], fetch_filtered_ids(&pool, &filter).await?);
+    for (stars, expected_ids) in [(0, vec![1, 2, 3, 4]), (2, vec![2, 3, 4]), (3, vec![3, 4])] {
+        let filter = TrackFilter {
+            rating_min: Rating::try_new(stars),
+            ..Default::default()
+        };
+        assert_eq!(expected_ids, fetch_filtered_ids(&pool, &filter).await?);
+    }
+    Ok(())
+}
+
+#[test]
+fn fetch_filtered() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime
+        .block_on(fetch_filtered_by_analyzed_bpm_and_stars())
+        .unwrap();
+}
@@ -0,0 +1,40 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+use endjine::{Track, TrackId, TrackMetadata, testing::create_test_database};
+
+async fn update_metadata_preserves_missing_fields() -> anyhow::Result<()> {
+    let (pool, _) = create_test_database().await?;
+    sqlx::query(
+        r#"INSERT INTO "Track" ("id","path","title","comment","label","composer","remixer","year")
+           VALUES (1,'../Music/1.mp3','Old title','Comment','Label','Composer','Remixer',1999)"#,
+    )
+    .execute(&pool)
+    .await?;
+    let id = TrackId::new(1);
+    let metadata = TrackMetadata {
+        title: Some("New title".to_owned()),
+        artist: Some("Artist".to_owned()),
+        ..Default::default()
+    };
+    assert!(Track::update_metadata(&pool, id, &metadata).await?);
+    let track = Track::try_load(&pool, id).await?.unwrap();
+    assert_eq!(Some("New title"), track.title.as_deref());
+    assert_eq!(Some("Artist"), track.artist.as_deref());
+    assert_eq!(None, track.album);
+    assert_eq!(Some("Comment"), track.comment.as_deref());
+    assert_eq!(Some("Label"), track.label.as_deref());
+    assert_eq!(Some("Composer"), track.composer.as_deref());
+    assert_eq!(Some("Remixer"), track.remixer.as_deref());
+    assert_eq!(Some(1999), track.year);
+    assert!(track.is_metadata_imported);
+    Ok(())
+}
+
+#[test]
+fn update_metadata_from_incomplete_tag() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime
+        .block_on(update_metadata_preserves_missing_fields())
+        .unwrap();
+}
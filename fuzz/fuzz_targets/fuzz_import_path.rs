@@ -0,0 +1,24 @@
+// SPDX-FileCopyrightText: The endjine authors
+// SPDX-License-Identifier: MPL-2.0
+
+#![no_main]
+
+use std::path::Path;
+
+use endjine::FilePath;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Arbitrary, non-UTF-8 paths are only supported on Unix.
+    #[cfg(unix)]
+    let path = {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt as _};
+        Path::new(OsStr::from_bytes(data))
+    };
+    #[cfg(not(unix))]
+    let path = String::from_utf8_lossy(data);
+    #[cfg(not(unix))]
+    let path = Path::new(path.as_ref());
+    // Must not panic.
+    let _file_path = FilePath::import_path(path);
+});